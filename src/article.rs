@@ -1,31 +1,134 @@
+use crate::models::{Alternate, ArticleLink, Author, ImageInfo, OpenGraph, SeriesInfo};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Article {
     pub title: String,
+    /// May be left out when dates come from git history; articles that end
+    /// up without one are skipped.
+    #[serde(default)]
     pub date: String,
+    #[serde(default)]
     pub update: String,
     pub summary: String,
     pub path: String,
     #[serde(default)]
     pub collection: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub translation_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alternates: Vec<Alternate>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub authors: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub author_profiles: Vec<Author>,
+    #[serde(
+        default,
+        deserialize_with = "crate::series::deserialize_series",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub series: Option<SeriesInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub series_order: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev: Option<ArticleLink>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next: Option<ArticleLink>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub site_prev: Option<ArticleLink>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub site_next: Option<ArticleLink>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cover: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cover_alt: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub og_description: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<ImageInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub og: Option<OpenGraph>,
+    /// Frontmatter keys without a dedicated field, passed through as-is.
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
 }
 
 impl Article {
     pub fn extract_frontmatter(content: &str) -> Option<(Article, &str)> {
+        Self::parse_frontmatter(content)?.ok()
+    }
+
+    /// Like [`extract_frontmatter`](Self::extract_frontmatter), but keeps
+    /// the TOML error of a frontmatter block that does not parse. Returns
+    /// `None` when there is no frontmatter block at all.
+    pub fn parse_frontmatter(content: &str) -> Option<Result<(Article, &str), toml::de::Error>> {
+        let (frontmatter_content, content_part) = Self::split_frontmatter(content)?;
+
+        Some(
+            toml::from_str::<Article>(frontmatter_content).map(|mut article| {
+                // Lint suppressions are read from the source, never exported.
+                article.extra.remove(crate::lint::IGNORE_KEY);
+                article.extra.values_mut().for_each(normalize_toml_value);
+                (article, content_part)
+            }),
+        )
+    }
+
+    /// Describes why the frontmatter of `content` failed to parse, with the
+    /// line and column of the problem in the whole file.
+    pub fn frontmatter_error(content: &str, error: &toml::de::Error) -> String {
+        let start = Self::split_frontmatter(content)
+            .map(|(frontmatter, _)| frontmatter.as_ptr() as usize - content.as_ptr() as usize)
+            .unwrap_or(0);
+        match error.span() {
+            Some(span) => {
+                let (line, column) = crate::lint::line_column(content, start + span.start);
+                format!(
+                    "invalid frontmatter at line {}, column {}: {}",
+                    line,
+                    column,
+                    error.message()
+                )
+            }
+            None => format!("invalid frontmatter: {}", error.message()),
+        }
+    }
+
+    /// Splits a Markdown file into its raw `+++` frontmatter and its body
+    /// without parsing either.
+    pub fn split_frontmatter(content: &str) -> Option<(&str, &str)> {
         if content.starts_with("+++") {
             let mut parts = content.split("\n+++\n");
             let frontmatter_part = parts.next()?;
             let content_part = parts.next().unwrap_or("");
 
-            let frontmatter_content = frontmatter_part.trim_start_matches("+++");
-
-            match toml::from_str::<Article>(frontmatter_content) {
-                Ok(article) => Some((article, content_part)),
-                Err(_) => None,
-            }
+            Some((frontmatter_part.trim_start_matches("+++"), content_part))
         } else {
             None
         }
     }
 }
+
+/// TOML datetimes arrive as a single-key marker object; turn them back into
+/// the plain string they were written as.
+fn normalize_toml_value(value: &mut Value) {
+    const DATETIME_KEY: &str = "$__toml_private_datetime";
+
+    match value {
+        Value::Object(map) => {
+            if map.len() == 1
+                && let Some(Value::String(datetime)) = map.get(DATETIME_KEY)
+            {
+                *value = Value::String(datetime.clone());
+            } else {
+                map.values_mut().for_each(normalize_toml_value);
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(normalize_toml_value),
+        _ => {}
+    }
+}
@@ -0,0 +1,101 @@
+use crate::models::ImageInfo;
+use pulldown_cmark::{Event, Parser, Tag};
+use sha2::{Digest, Sha256};
+use std::ops::Range;
+use std::path::Path;
+
+/// Returns the image sources referenced by a Markdown body, in order of first
+/// appearance and without duplicates.
+pub fn image_sources(body: &str) -> Vec<String> {
+    let mut sources: Vec<String> = Vec::new();
+
+    for event in Parser::new(body) {
+        if let Event::Start(Tag::Image { dest_url, .. }) = event
+            && !sources.iter().any(|s| s == dest_url.as_ref())
+        {
+            sources.push(dest_url.to_string());
+        }
+    }
+
+    sources
+}
+
+/// Remote and inline images are left to the browser.
+pub fn is_external(src: &str) -> bool {
+    src.starts_with("http://")
+        || src.starts_with("https://")
+        || src.starts_with("//")
+        || src.starts_with("data:")
+}
+
+/// Strips any query string or fragment so the source can be looked up on disk.
+pub fn local_part(src: &str) -> &str {
+    src.split(['?', '#']).next().unwrap_or(src)
+}
+
+/// Builds the content-hashed file name for an image, e.g.
+/// `diagram.3f2a9c01b4d5e6f7.png`.
+pub fn fingerprinted_name(file_path: &Path, content: &[u8]) -> String {
+    let digest = Sha256::digest(content);
+    let hash: String = digest[..8].iter().map(|b| format!("{:02x}", b)).collect();
+
+    let stem = file_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("image");
+
+    match file_path.extension().and_then(|s| s.to_str()) {
+        Some(ext) => format!("{}.{}.{}", stem, hash, ext),
+        None => format!("{}.{}", stem, hash),
+    }
+}
+
+/// Rewrites image destinations in a Markdown body to their fingerprinted
+/// paths. Both inline images and reference definitions used by images are
+/// rewritten; everything else is kept byte-for-byte.
+///
+/// The build never calls this: Markdown sources are left untouched and
+/// rendered HTML resolves images on its own. It is offered to callers that
+/// publish the Markdown bodies themselves.
+pub fn rewrite_image_links(body: &str, images: &[ImageInfo], prefix: &str) -> String {
+    let parser = Parser::new(body);
+    let mut replacements: Vec<(Range<usize>, String)> = Vec::new();
+
+    for (_, def) in parser.reference_definitions().iter() {
+        if let Some(image) = images.iter().find(|i| i.src == def.dest.as_ref())
+            && let Some(range) = find_within(body, def.span.clone(), &image.src)
+        {
+            replacements.push((range, format!("{}{}", prefix, image.path)));
+        }
+    }
+
+    for (event, range) in parser.into_offset_iter() {
+        if let Event::Start(Tag::Image { dest_url, .. }) = event
+            && let Some(image) = images.iter().find(|i| i.src == dest_url.as_ref())
+            && let Some(range) = find_within(body, range, &image.src)
+        {
+            replacements.push((range, format!("{}{}", prefix, image.path)));
+        }
+    }
+
+    replacements.sort_by_key(|(range, _)| range.start);
+    replacements.dedup_by_key(|(range, _)| range.start);
+
+    let mut rewritten = String::with_capacity(body.len());
+    let mut last = 0;
+    for (range, replacement) in replacements {
+        rewritten.push_str(&body[last..range.start]);
+        rewritten.push_str(&replacement);
+        last = range.end;
+    }
+    rewritten.push_str(&body[last..]);
+
+    rewritten
+}
+
+fn find_within(body: &str, span: Range<usize>, needle: &str) -> Option<Range<usize>> {
+    let slice = body.get(span.clone())?;
+    let search_from = slice.find("](").map(|i| i + 2).unwrap_or(0);
+    let start = span.start + search_from + slice[search_from..].find(needle)?;
+    Some(start..start + needle.len())
+}
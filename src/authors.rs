@@ -0,0 +1,67 @@
+use crate::error::{Result, WithPath, ZoneError};
+use crate::models::Author;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+pub const AUTHORS_FILE_NAME: &str = "authors.toml";
+
+#[derive(Debug, Deserialize)]
+struct AuthorEntry {
+    name: String,
+    #[serde(default)]
+    bio: Option<String>,
+    #[serde(default)]
+    avatar: Option<String>,
+    #[serde(default)]
+    links: BTreeMap<String, String>,
+}
+
+/// Loads the author profiles defined in `authors.toml` at the blog root,
+/// keyed by id. A missing file means no authors are defined.
+pub fn load_authors(blog_dir: &str) -> Result<BTreeMap<String, Author>> {
+    let path = Path::new(blog_dir).join(AUTHORS_FILE_NAME);
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+
+    let content = fs::read_to_string(&path).with_path(&path)?;
+    let entries: BTreeMap<String, AuthorEntry> = toml::from_str(&content).with_path(&path)?;
+
+    Ok(entries
+        .into_iter()
+        .map(|(id, entry)| {
+            let author = Author {
+                id: id.clone(),
+                name: entry.name,
+                bio: entry.bio,
+                avatar: entry.avatar,
+                links: entry.links,
+            };
+            (id, author)
+        })
+        .collect())
+}
+
+/// Looks up every author id referenced by an article, failing on the first
+/// id that has no profile.
+pub fn resolve_authors(
+    ids: &[String],
+    authors: &BTreeMap<String, Author>,
+    source: &Path,
+) -> Result<Vec<Author>> {
+    ids.iter()
+        .map(|id| {
+            authors.get(id).cloned().ok_or_else(|| {
+                ZoneError::validation(
+                    source,
+                    format!(
+                        "unknown author '{}' (not defined in {})",
+                        id, AUTHORS_FILE_NAME
+                    ),
+                )
+            })
+        })
+        .collect()
+}
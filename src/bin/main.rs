@@ -1,19 +1,412 @@
-use clap::Parser;
-use zone_builder::BlogProcessor;
+use clap::{ArgAction, Parser, Subcommand, ValueEnum};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+use zone_builder::config::CONFIG_FILE_NAME;
+use zone_builder::error;
+use zone_builder::logging::{self, LogFormat};
+use zone_builder::{BlogProcessor, Config, WriteSummary, scaffold, serve, touch, watch};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
-    #[arg(short, long, default_value = ".")]
+    #[arg(short, long, default_value = ".", global = true)]
     path: String,
+
+    /// Configuration file (default: zone.toml in the blog directory)
+    #[arg(short, long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Directory receiving the generated files (default: the blog directory)
+    #[arg(short, long, global = true)]
+    output: Option<PathBuf>,
+
+    /// Log every processed file and written output; repeat for even more
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    verbose: u8,
+
+    /// Only log warnings and errors
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Format of the log lines written to stderr
+    #[arg(long, value_enum, default_value_t = LogFormatArg::Text, global = true)]
+    log_format: LogFormatArg,
+
+    /// Ignore the build cache and rewrite every output file
+    #[arg(long, global = true)]
+    force: bool,
+
+    /// Number of threads used to parse Markdown files (default: one per CPU)
+    #[arg(short, long, default_value_t = 0, global = true)]
+    jobs: usize,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Build the site (default when no subcommand is given)
+    Build {
+        /// Write a report of every source, output, phase timing and
+        /// diagnostic to this file, or to stdout with `-`
+        #[arg(long)]
+        report: Option<PathBuf>,
+
+        #[arg(long, value_enum, default_value_t = ReportFormat::Json, requires = "report")]
+        report_format: ReportFormat,
+    },
+    /// Report the generated files a build would create, modify or delete,
+    /// without writing anything, and lint every article and its links; exits
+    /// with status 1 when generated JSON is out of date, a lint rule reports an
+    /// error or a link is broken
+    Check {
+        /// Print a unified diff for every change
+        #[arg(long)]
+        diff: bool,
+
+        #[arg(long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+    },
+    /// Create a new article with frontmatter
+    New {
+        /// Collection directory, e.g. `tech` or `tech/rust`
+        collection: String,
+        title: String,
+    },
+    /// Set the `update` date of articles to today, then rebuild
+    #[command(alias = "bump")]
+    Touch {
+        /// Articles to update
+        #[arg(required_unless_present = "changed")]
+        articles: Vec<PathBuf>,
+
+        /// Update every article whose content changed since the last build
+        #[arg(long, conflicts_with = "articles")]
+        changed: bool,
+
+        /// Date to set instead of today, e.g. 2024-03-01
+        #[arg(long)]
+        date: Option<String>,
+    },
+    /// List articles, newest first
+    List {
+        /// Only articles in this collection or below it
+        #[arg(long)]
+        collection: Option<String>,
+
+        #[arg(long)]
+        lang: Option<String>,
+
+        /// Only articles by this author id
+        #[arg(long)]
+        author: Option<String>,
+
+        /// Only articles in this series
+        #[arg(long)]
+        series: Option<String>,
+
+        /// Print the articles as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Remove every generated file
+    Clean,
+    /// Build, then rebuild whenever Markdown files, name.txt or configuration change
+    Watch {
+        /// Milliseconds to wait for a burst of changes to settle
+        #[arg(long, default_value_t = 300)]
+        debounce_ms: u64,
+    },
+    /// Build, serve the blog directory over HTTP and live-reload on changes
+    Serve {
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
+
+        /// Value of the Access-Control-Allow-Origin header
+        #[arg(long, default_value = "*")]
+        cors_origin: String,
+
+        /// Milliseconds to wait for a burst of changes to settle
+        #[arg(long, default_value_t = 300)]
+        debounce_ms: u64,
+    },
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    Text,
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum LogFormatArg {
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum ReportFormat {
+    Json,
+    /// SARIF 2.1.0, for code scanning annotations
+    Sarif,
+}
+
+/// Exit status of a `check` that found out-of-date outputs or lint errors.
+const CHECK_FAILED: u8 = 1;
+/// Exit status of any command that failed with an error.
+const ERROR: u8 = 2;
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(code) => code,
+        Err(e) => {
+            log::error!("{}", error::chain(&*e));
+            ExitCode::from(ERROR)
+        }
+    }
+}
+
+fn run() -> Result<ExitCode, Box<dyn std::error::Error>> {
     let args = Args::parse();
 
-    let processor = BlogProcessor::new(args.path);
-    processor.process_directory()?;
+    let level = match (args.quiet, args.verbose) {
+        (true, _) => log::LevelFilter::Warn,
+        (false, 0) => log::LevelFilter::Info,
+        (false, 1) => log::LevelFilter::Debug,
+        (false, _) => log::LevelFilter::Trace,
+    };
+    let log_format = match args.log_format {
+        LogFormatArg::Text => LogFormat::Text,
+        LogFormatArg::Json => LogFormat::Json,
+    };
+    logging::init(level, log_format)?;
+
+    let config_path = args
+        .config
+        .clone()
+        .unwrap_or_else(|| Path::new(&args.path).join(CONFIG_FILE_NAME));
+    let config = Config::load_from(&config_path)?;
+
+    let mut processor = BlogProcessor::with_config(args.path.clone(), config);
+    processor.force = args.force;
+    processor.jobs = args.jobs;
+    processor.output_dir = args.output.clone();
 
-    println!("Blog directory processing completed.");
+    let command = args.command.unwrap_or(Command::Build {
+        report: None,
+        report_format: ReportFormat::Json,
+    });
+    match command {
+        Command::Build {
+            report: None,
+            report_format: _,
+        } => build(&processor)?,
+        Command::Build {
+            report: Some(report_path),
+            report_format,
+        } => {
+            let report = processor.build_report();
+            let contents = match report_format {
+                ReportFormat::Json => serde_json::to_string_pretty(&report)?,
+                ReportFormat::Sarif => {
+                    serde_json::to_string_pretty(&report.to_sarif(Path::new(&args.path)))?
+                }
+            };
+            if report_path == Path::new("-") {
+                println!("{}", contents);
+            } else {
+                std::fs::write(&report_path, contents + "\n")?;
+            }
+
+            if let Some(error) = report.error {
+                return Err(error.into());
+            }
+            log_completed(&report.summary);
+        }
+        Command::Check { diff, format } => {
+            let report = processor.check()?;
+            let root = processor.output_root();
+            let out_of_date = report.out_of_date().count();
+            let lint_errors = report.lint_errors().count();
+
+            if format == Format::Json {
+                let changes: Vec<_> = report
+                    .changes
+                    .iter()
+                    .map(|change| {
+                        let path = change.path.strip_prefix(&root).unwrap_or(&change.path);
+                        let mut entry = serde_json::json!({
+                            "path": path.to_string_lossy().replace('\\', "/"),
+                            "kind": change.kind.to_string(),
+                        });
+                        if diff {
+                            entry["diff"] = change.unified_diff(&root).into();
+                        }
+                        entry
+                    })
+                    .collect();
+                let output = serde_json::json!({
+                    "changes": changes,
+                    "diagnostics": report.diagnostics,
+                    "external_links": report.external_links,
+                });
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                for change in &report.changes {
+                    let path = change.path.strip_prefix(&root).unwrap_or(&change.path);
+                    println!("{:>8}  {}", change.kind, path.display());
+                    if diff {
+                        print!("{}", change.unified_diff(&root));
+                    }
+                }
+                for diagnostic in &report.diagnostics {
+                    println!("{}", diagnostic);
+                }
+                for link in &report.external_links {
+                    log::debug!(
+                        file = link.file.as_str(),
+                        line = link.line,
+                        column = link.column,
+                        url = link.url.as_str();
+                        "{}:{}:{}: external link {} (not checked)",
+                        link.file, link.line, link.column, link.url
+                    );
+                }
+            }
+
+            if out_of_date > 0 {
+                log::error!(
+                    "{} generated JSON files are out of date; run `zone-builder build` to update them.",
+                    out_of_date
+                );
+            }
+            if lint_errors > 0 {
+                log::error!("{} lint errors found.", lint_errors);
+            }
+            if out_of_date > 0 || lint_errors > 0 {
+                return Ok(ExitCode::from(CHECK_FAILED));
+            }
+            log::info!("Check passed: generated JSON files are up to date.");
+        }
+        Command::New { collection, title } => {
+            let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+            let path = scaffold::new_article(Path::new(&args.path), &collection, &title, &date)?;
+            println!("Created {}", path.display());
+        }
+        Command::Touch {
+            articles,
+            changed,
+            date,
+        } => {
+            let articles = if changed {
+                touch::changed_since_build(&args.path)?
+            } else {
+                articles
+            };
+            let date = date.unwrap_or_else(|| chrono::Local::now().format("%Y-%m-%d").to_string());
+            let touched = touch::touch(&articles, &date)?;
+
+            for path in &touched {
+                log::info!("Updated {}", path.display());
+            }
+            log::info!("Set update to {} in {} articles.", date, touched.len());
+            build(&processor)?;
+        }
+        Command::List {
+            collection,
+            lang,
+            author,
+            series,
+            json,
+        } => {
+            let articles: Vec<_> = processor
+                .articles()?
+                .into_iter()
+                .filter(|a| {
+                    collection.as_ref().is_none_or(|c| {
+                        a.collection.as_ref().is_some_and(|ac| {
+                            ac == c || ac.starts_with(&format!("{}/", c.trim_end_matches('/')))
+                        })
+                    })
+                })
+                .filter(|a| lang.as_ref().is_none_or(|l| a.lang.as_ref() == Some(l)))
+                .filter(|a| author.as_ref().is_none_or(|id| a.authors.contains(id)))
+                .filter(|a| {
+                    series
+                        .as_ref()
+                        .is_none_or(|s| a.series.as_ref().is_some_and(|info| info.name == *s))
+                })
+                .collect();
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&articles)?);
+            } else {
+                for article in &articles {
+                    let location = match &article.collection {
+                        Some(collection) => format!("{}/{}", collection, article.path),
+                        None => article.path.clone(),
+                    };
+                    println!("{}  {}  {}", article.date, location, article.title);
+                }
+            }
+        }
+        Command::Clean => {
+            let removed = processor.clean()?;
+            for path in &removed {
+                log::debug!("Removed {}", path.display());
+            }
+            log::info!("Removed {} generated files.", removed.len());
+        }
+        Command::Watch { debounce_ms } => {
+            build(&processor)?;
+            processor.force = false;
+            watch::watch(
+                &mut processor,
+                &config_path,
+                Duration::from_millis(debounce_ms),
+                |_| {},
+            )?;
+        }
+        Command::Serve {
+            host,
+            port,
+            cors_origin,
+            debounce_ms,
+        } => {
+            build(&processor)?;
+            processor.force = false;
+            serve::serve(
+                &mut processor,
+                &config_path,
+                &format!("{}:{}", host, port),
+                &cors_origin,
+                Duration::from_millis(debounce_ms),
+            )?;
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn build(processor: &BlogProcessor) -> Result<(), Box<dyn std::error::Error>> {
+    let summary = processor.process_directory()?;
+    log_completed(&summary);
     Ok(())
 }
+
+fn log_completed(summary: &WriteSummary) {
+    log::info!(
+        written = summary.written,
+        unchanged = summary.unchanged;
+        "Blog directory processing completed: {} files written, {} unchanged.",
+        summary.written,
+        summary.unchanged
+    );
+}
@@ -0,0 +1,107 @@
+use crate::Article;
+use crate::error::{Result, WithPath};
+use crate::output;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+pub const CACHE_FILE_NAME: &str = ".zone-cache";
+
+/// Bumped whenever the cached data changes shape, so stale caches from older
+/// builds are ignored instead of misread.
+const CACHE_VERSION: u32 = 4;
+
+/// Parse results of the previous build, keyed by the Markdown file's path
+/// relative to the blog root, and the outputs it produced.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BuildCache {
+    pub version: u32,
+    pub entries: BTreeMap<String, CacheEntry>,
+    /// Every file the build wrote or kept, relative to the output root.
+    /// Only these are ever removed as stale or by `clean`.
+    #[serde(default)]
+    pub outputs: BTreeSet<String>,
+    /// The output directory in effect for `outputs`, `None` for the blog root.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub hash: String,
+    pub mtime: u64,
+    pub size: u64,
+    /// The article as parsed from frontmatter, before any enrichment, or
+    /// `None` when the file has no valid frontmatter.
+    pub article: Option<Article>,
+    /// Why the frontmatter did not parse, when there is a frontmatter block.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub image_sources: Vec<String>,
+}
+
+impl Default for BuildCache {
+    fn default() -> Self {
+        Self {
+            version: CACHE_VERSION,
+            entries: BTreeMap::new(),
+            outputs: BTreeSet::new(),
+            output_dir: None,
+        }
+    }
+}
+
+impl BuildCache {
+    /// Loads the cache from the blog root. A missing, unreadable or outdated
+    /// cache simply means a cold build.
+    pub fn load(blog_dir: &str) -> Self {
+        fs::read_to_string(Path::new(blog_dir).join(CACHE_FILE_NAME))
+            .ok()
+            .and_then(|content| serde_json::from_str::<BuildCache>(&content).ok())
+            .filter(|cache| cache.version == CACHE_VERSION)
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, blog_dir: &str) -> Result<()> {
+        let path = Path::new(blog_dir).join(CACHE_FILE_NAME);
+        let json_content = serde_json::to_string(self).with_path(&path)?;
+        output::write_atomic(&path, json_content.as_bytes())
+    }
+
+    /// Returns the entry for a file whose modification time and size are
+    /// unchanged, which lets the caller skip reading it altogether.
+    pub fn fresh(&self, key: &str, mtime: u64, size: u64) -> Option<&CacheEntry> {
+        self.entries
+            .get(key)
+            .filter(|entry| entry.mtime == mtime && entry.size == size)
+    }
+
+    /// Returns the entry for a file whose content is unchanged even though
+    /// it was touched.
+    pub fn matching(&self, key: &str, hash: &str) -> Option<&CacheEntry> {
+        self.entries.get(key).filter(|entry| entry.hash == hash)
+    }
+}
+
+pub fn content_hash(content: &[u8]) -> String {
+    Sha256::digest(content)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Modification time in nanoseconds since the epoch, and size in bytes.
+pub fn file_stamp(metadata: &fs::Metadata) -> (u64, u64) {
+    let mtime = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_nanos() as u64)
+        .unwrap_or(0);
+
+    (mtime, metadata.len())
+}
@@ -0,0 +1,98 @@
+use crate::links::ExternalLink;
+use crate::lint::{Diagnostic, Severity};
+use crate::output::WriteSummary;
+use similar::TextDiff;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// What a build would do to the generated files, as found by
+/// [`BlogProcessor::check`](crate::BlogProcessor::check).
+#[derive(Debug, Default)]
+pub struct CheckReport {
+    pub summary: WriteSummary,
+    /// Created, modified and deleted files, sorted by path.
+    pub changes: Vec<FileChange>,
+    /// Lint findings and broken links, sorted by file and position.
+    pub diagnostics: Vec<Diagnostic>,
+    /// Links leaving the blog, which are not checked.
+    pub external_links: Vec<ExternalLink>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FileChange {
+    pub path: PathBuf,
+    pub kind: ChangeKind,
+    /// Current contents; `None` for created files.
+    pub old: Option<Vec<u8>>,
+    /// Contents after the build; `None` for deleted files.
+    pub new: Option<Vec<u8>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Created,
+    Modified,
+    /// Generated by an earlier build but no longer produced, e.g. the
+    /// `meta.json` of a collection whose articles were all removed.
+    Deleted,
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ChangeKind::Created => "created",
+            ChangeKind::Modified => "modified",
+            ChangeKind::Deleted => "deleted",
+        })
+    }
+}
+
+impl CheckReport {
+    /// The changes to generated JSON files, which are expected to be
+    /// committed alongside the Markdown sources.
+    pub fn out_of_date(&self) -> impl Iterator<Item = &FileChange> {
+        self.changes
+            .iter()
+            .filter(|c| c.path.extension().is_some_and(|ext| ext == "json"))
+    }
+
+    /// The diagnostics from rules configured as errors, including broken
+    /// links.
+    pub fn lint_errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Error)
+    }
+}
+
+impl FileChange {
+    /// Renders the change as a unified diff with paths shown relative to
+    /// `root`. Binary files are only reported as differing.
+    pub fn unified_diff(&self, root: &Path) -> String {
+        let name = self
+            .path
+            .strip_prefix(root)
+            .unwrap_or(&self.path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let old_name = match self.kind {
+            ChangeKind::Created => "/dev/null".to_string(),
+            _ => format!("a/{}", name),
+        };
+        let new_name = match self.kind {
+            ChangeKind::Deleted => "/dev/null".to_string(),
+            _ => format!("b/{}", name),
+        };
+
+        let old = std::str::from_utf8(self.old.as_deref().unwrap_or_default());
+        let new = std::str::from_utf8(self.new.as_deref().unwrap_or_default());
+        match (old, new) {
+            (Ok(old), Ok(new)) => TextDiff::from_lines(old, new)
+                .unified_diff()
+                .context_radius(3)
+                .header(&old_name, &new_name)
+                .to_string(),
+            _ => format!("Binary files {} and {} differ\n", old_name, new_name),
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use crate::error::{Result, WithPath};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const CONFIG_FILE_NAME: &str = "zone.toml";
+
+#[derive(Debug, Default, Deserialize, Clone)]
+#[serde(default)]
+pub struct Config {
+    pub collections: CollectionConfig,
+    pub assets: AssetsConfig,
+    pub images: ImagesConfig,
+    pub render: RenderConfig,
+    pub extra: ExtraConfig,
+    pub navigation: NavigationConfig,
+    pub i18n: I18nConfig,
+    pub dates: DatesConfig,
+    pub lint: LintConfig,
+    /// The file this configuration was loaded from, whether or not it
+    /// existed; `None` for a configuration built in code.
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+}
+
+#[derive(Debug, Default, Deserialize, Clone)]
+#[serde(default)]
+pub struct CollectionConfig {
+    /// When set, a collection's `meta.json` also lists the articles of all
+    /// nested collections below it.
+    pub include_descendants: bool,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct AssetsConfig {
+    /// Directory, relative to the blog root, that receives fingerprinted
+    /// copies of referenced images together with `manifest.json`.
+    pub output_dir: String,
+}
+
+impl Default for AssetsConfig {
+    fn default() -> Self {
+        Self {
+            output_dir: "assets".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct ImagesConfig {
+    /// Widths, in pixels, of the resized variants generated for each image.
+    /// Widths at or above the original width are skipped.
+    pub widths: Vec<u32>,
+    /// Extra encodings to generate besides the original format. `avif` is
+    /// only available when built with the `avif` feature.
+    pub formats: Vec<String>,
+}
+
+impl Default for ImagesConfig {
+    fn default() -> Self {
+        Self {
+            widths: vec![480, 960, 1440],
+            formats: vec!["webp".to_string()],
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Clone)]
+#[serde(default)]
+pub struct RenderConfig {
+    /// Writes an `.html` rendering next to every article.
+    pub html: bool,
+}
+
+/// Controls which unknown frontmatter keys are exported. The top-level
+/// `include`/`exclude` lists apply to every output file unless the file has
+/// its own entry under `files`, keyed by name (`meta.json`, `index.json`, ...).
+#[derive(Debug, Default, Deserialize, Clone)]
+#[serde(default)]
+pub struct ExtraConfig {
+    #[serde(flatten)]
+    pub default: ExtraFilter,
+    pub files: BTreeMap<String, ExtraFilter>,
+}
+
+#[derive(Debug, Default, Deserialize, Clone)]
+#[serde(default)]
+pub struct ExtraFilter {
+    /// Keys to export; an empty list exports every key not excluded.
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl ExtraConfig {
+    pub fn allows(&self, file_name: &str, key: &str) -> bool {
+        let filter = self.files.get(file_name).unwrap_or(&self.default);
+        (filter.include.is_empty() || filter.include.iter().any(|k| k == key))
+            && !filter.exclude.iter().any(|k| k == key)
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Clone)]
+#[serde(default)]
+pub struct NavigationConfig {
+    /// Adds `site_prev`/`site_next` links across all articles to `index.json`.
+    pub site_wide: bool,
+}
+
+#[derive(Debug, Default, Deserialize, Clone)]
+#[serde(default)]
+pub struct DatesConfig {
+    /// Defaults `date` to the commit that added an article and `update` to
+    /// the last commit that changed it. Frontmatter values still win.
+    pub from_git: bool,
+}
+
+/// `[lint.rules.<id>]` tables for the rules run by `check`. Each table may
+/// set `severity` (`off`, `warning` or `error`) next to the rule's own
+/// options; `<id> = "off"` is a shorthand for the severity alone.
+#[derive(Debug, Default, Deserialize, Clone)]
+#[serde(default)]
+pub struct LintConfig {
+    pub rules: BTreeMap<String, toml::Value>,
+}
+
+/// Multilingual content is enabled by listing more than one language.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct I18nConfig {
+    /// Language of articles without a `lang` field or file name suffix.
+    pub default_language: String,
+    /// Languages recognised as file name suffixes (`post.en.md`); each one
+    /// gets its own `index.<lang>.json`, `meta.<lang>.json` and feeds.
+    pub languages: Vec<String>,
+}
+
+impl Default for I18nConfig {
+    fn default() -> Self {
+        Self {
+            default_language: "zh".to_string(),
+            languages: Vec::new(),
+        }
+    }
+}
+
+impl I18nConfig {
+    pub fn enabled(&self) -> bool {
+        !self.languages.is_empty()
+    }
+}
+
+impl Config {
+    /// Loads `zone.toml` from the blog root, falling back to defaults when
+    /// the file does not exist.
+    pub fn load(blog_dir: &str) -> Result<Self> {
+        Self::load_from(&Path::new(blog_dir).join(CONFIG_FILE_NAME))
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let mut config: Self = if path.exists() {
+            let content = fs::read_to_string(path).with_path(path)?;
+            toml::from_str(&content).with_path(path)?
+        } else {
+            Self::default()
+        };
+        config.path = Some(path.to_path_buf());
+        Ok(config)
+    }
+}
@@ -0,0 +1,132 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// The error type of every fallible operation in this crate. Each variant
+/// names the file or directory involved; the underlying cause, where there is
+/// one, is available through [`std::error::Error::source`] rather than
+/// repeated in the message, so [`chain`] gives the full story.
+#[derive(Debug, Error)]
+pub enum ZoneError {
+    /// Reading, writing or inspecting a file failed.
+    #[error("I/O error on {}", .path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    /// A JSON file could not be parsed or serialized.
+    #[error("invalid JSON in {}", .path.display())]
+    Json {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+    /// A TOML file could not be parsed.
+    #[error("invalid TOML in {}", .path.display())]
+    Toml {
+        path: PathBuf,
+        #[source]
+        source: toml::de::Error,
+    },
+    /// Walking a directory tree failed.
+    #[error("cannot walk {}", .path.display())]
+    Walk {
+        path: PathBuf,
+        #[source]
+        source: walkdir::Error,
+    },
+    /// The configuration parsed but holds a value that cannot be used.
+    #[error("{}: {message}", .path.display())]
+    Config { path: PathBuf, message: String },
+    /// Content breaks a rule of the blog, e.g. an unknown author or a
+    /// conflicting series order.
+    #[error("{}: {message}", .path.display())]
+    Validation { path: PathBuf, message: String },
+}
+
+pub type Result<T, E = ZoneError> = std::result::Result<T, E>;
+
+impl ZoneError {
+    /// The file or directory the error is about, as the crate accessed it:
+    /// files of a blog are joined onto the blog or output directory given to
+    /// [`BlogProcessor`](crate::BlogProcessor), so they are relative exactly
+    /// when that directory is.
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Io { path, .. }
+            | Self::Json { path, .. }
+            | Self::Toml { path, .. }
+            | Self::Walk { path, .. }
+            | Self::Config { path, .. }
+            | Self::Validation { path, .. } => path,
+        }
+    }
+
+    pub(crate) fn config(path: impl Into<PathBuf>, message: impl Into<String>) -> Self {
+        Self::Config {
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+
+    pub(crate) fn validation(path: impl Into<PathBuf>, message: impl Into<String>) -> Self {
+        Self::Validation {
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl From<walkdir::Error> for ZoneError {
+    fn from(source: walkdir::Error) -> Self {
+        Self::Walk {
+            path: source.path().map(Path::to_path_buf).unwrap_or_default(),
+            source,
+        }
+    }
+}
+
+/// Attaches the path being worked on to a foreign error.
+pub(crate) trait WithPath<T> {
+    fn with_path(self, path: impl AsRef<Path>) -> Result<T>;
+}
+
+impl<T> WithPath<T> for std::result::Result<T, io::Error> {
+    fn with_path(self, path: impl AsRef<Path>) -> Result<T> {
+        self.map_err(|source| ZoneError::Io {
+            path: path.as_ref().to_path_buf(),
+            source,
+        })
+    }
+}
+
+impl<T> WithPath<T> for std::result::Result<T, serde_json::Error> {
+    fn with_path(self, path: impl AsRef<Path>) -> Result<T> {
+        self.map_err(|source| ZoneError::Json {
+            path: path.as_ref().to_path_buf(),
+            source,
+        })
+    }
+}
+
+impl<T> WithPath<T> for std::result::Result<T, toml::de::Error> {
+    fn with_path(self, path: impl AsRef<Path>) -> Result<T> {
+        self.map_err(|source| ZoneError::Toml {
+            path: path.as_ref().to_path_buf(),
+            source,
+        })
+    }
+}
+
+/// Renders an error followed by each of its causes, separated by `: `.
+pub fn chain(error: &dyn std::error::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    message
+}
@@ -0,0 +1,92 @@
+use crate::Article;
+use crate::error::{Result, WithPath};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+/// When a file was first added and last changed, as `YYYY-MM-DD` author
+/// dates from the local git history.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitDates {
+    pub created: String,
+    pub updated: String,
+}
+
+/// Reads the dates of every file below `blog_dir` from a single `git log`,
+/// keyed by `/`-separated paths relative to `blog_dir`. Renames are not
+/// followed, so a moved file starts a new history. Fails when git is not
+/// installed or `blog_dir` is not inside a repository.
+pub fn load_git_dates(blog_dir: &Path) -> Result<BTreeMap<String, GitDates>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(blog_dir)
+        .args([
+            "-c",
+            "core.quotePath=false",
+            "log",
+            "--relative",
+            "--no-renames",
+            "--name-only",
+            "--date=short",
+            "--format=%x00%ad",
+            "--",
+            ".",
+        ])
+        .output()
+        .with_path(blog_dir)?;
+
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "git log failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+        .with_path(blog_dir);
+    }
+
+    Ok(parse_log(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parses `git log` output, newest commit first: the first date seen for a
+/// file is its last change and the last one seen is when it was added.
+fn parse_log(log: &str) -> BTreeMap<String, GitDates> {
+    let mut dates: BTreeMap<String, GitDates> = BTreeMap::new();
+    let mut current = "";
+
+    for line in log.lines() {
+        if let Some(date) = line.strip_prefix('\0') {
+            current = date.trim();
+        } else if !line.is_empty() && !current.is_empty() {
+            dates
+                .entry(line.to_string())
+                .and_modify(|d| d.created = current.to_string())
+                .or_insert_with(|| GitDates {
+                    created: current.to_string(),
+                    updated: current.to_string(),
+                });
+        }
+    }
+
+    dates
+}
+
+/// Fills an empty `date` or `update` from the file's history. Values from
+/// the frontmatter win; a warning is returned for each one naming another
+/// day than the history.
+pub fn apply_git_dates(article: &mut Article, dates: &GitDates) -> Vec<String> {
+    let mut warnings = Vec::new();
+    for (field, value, from_git) in [
+        ("date", &mut article.date, &dates.created),
+        ("update", &mut article.update, &dates.updated),
+    ] {
+        if value.is_empty() {
+            *value = from_git.clone();
+        } else if value.get(..10).unwrap_or(value) != from_git {
+            warnings.push(format!(
+                "Frontmatter {} {} disagrees with git history ({})",
+                field, value, from_git
+            ));
+        }
+    }
+    warnings
+}
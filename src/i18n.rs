@@ -0,0 +1,85 @@
+use crate::Article;
+use crate::config::I18nConfig;
+use crate::models::{Alternate, ArticleLink};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Splits a language suffix off a file stem, e.g. `post.en` becomes
+/// `("post", Some("en"))`. Only configured languages count as suffixes.
+pub fn split_language_suffix<'a>(stem: &'a str, config: &I18nConfig) -> (&'a str, Option<&'a str>) {
+    match stem.rsplit_once('.') {
+        Some((base, lang)) if config.languages.iter().any(|l| l == lang) => (base, Some(lang)),
+        _ => (stem, None),
+    }
+}
+
+/// Fills in `lang` and `translation_key` for an article. Frontmatter values
+/// win; otherwise the language comes from the file name suffix or the default
+/// language, and the key from the collection and the file name without its
+/// language suffix.
+pub fn assign_language(article: &mut Article, source: &Path, config: &I18nConfig) {
+    let stem = source
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(&article.path);
+    let (base, suffix) = split_language_suffix(stem, config);
+
+    if article.lang.is_none() {
+        article.lang = Some(
+            suffix
+                .map(str::to_string)
+                .unwrap_or_else(|| config.default_language.clone()),
+        );
+    }
+
+    if article.translation_key.is_none() {
+        article.translation_key = Some(match &article.collection {
+            Some(collection) => format!("{}/{}", collection, base),
+            None => base.to_string(),
+        });
+    }
+}
+
+/// Lists, on every article, the other languages its translation key is
+/// available in.
+pub fn attach_alternates(articles: &mut [Article]) {
+    let mut by_key: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for (idx, article) in articles.iter().enumerate() {
+        if let Some(key) = &article.translation_key {
+            by_key.entry(key.clone()).or_default().push(idx);
+        }
+    }
+
+    for members in by_key.values() {
+        let mut translations: Vec<Alternate> = members
+            .iter()
+            .filter_map(|idx| {
+                let article = &articles[*idx];
+                article.lang.as_ref().map(|lang| Alternate {
+                    lang: lang.clone(),
+                    link: ArticleLink::from(article),
+                })
+            })
+            .collect();
+        translations.sort_by(|a, b| a.lang.cmp(&b.lang));
+
+        for idx in members {
+            let lang = articles[*idx].lang.clone();
+            articles[*idx].alternates = translations
+                .iter()
+                .filter(|t| Some(&t.lang) != lang.as_ref())
+                .cloned()
+                .collect();
+        }
+    }
+}
+
+/// Inserts the language before the extension, e.g. `index.json` becomes
+/// `index.en.json`.
+pub fn localized_file_name(file_name: &str, lang: Option<&str>) -> String {
+    match (lang, file_name.rsplit_once('.')) {
+        (Some(lang), Some((stem, ext))) => format!("{}.{}.{}", stem, lang, ext),
+        (Some(lang), None) => format!("{}.{}", file_name, lang),
+        (None, _) => file_name.to_string(),
+    }
+}
@@ -0,0 +1,251 @@
+use crate::config::ImagesConfig;
+use crate::error::{Result, WithPath, ZoneError};
+use crate::models::{ImageInfo, ImageVariant};
+use crate::output::OutputWriter;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageError, ImageFormat};
+use pulldown_cmark::{Event, Parser, Tag, TagEnd, html};
+use std::fs;
+use std::io::{self, Cursor};
+use std::path::Path;
+
+/// Generates resized and re-encoded variants of a fingerprinted image next to
+/// `image_path`, decoding `content`, the image itself. Variants that already
+/// exist are reused without decoding, since their names derive from the
+/// content hash. New variants go through `output`, so they are staged with
+/// the rest of the build and only planned during a dry run.
+pub fn generate_variants(
+    image_path: &Path,
+    content: &[u8],
+    path_prefix: &str,
+    width: usize,
+    height: usize,
+    config: &ImagesConfig,
+    output: &OutputWriter,
+) -> Result<Vec<ImageVariant>> {
+    let Some(original_format) = ImageFormat::from_path(image_path).ok() else {
+        return Ok(Vec::new());
+    };
+    let dir = image_path.parent().unwrap_or(Path::new("."));
+    let base = image_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("image");
+
+    let mut formats = vec![original_format];
+    for name in &config.formats {
+        match variant_format(name) {
+            Some(format) if format != original_format => formats.push(format),
+            Some(_) => {}
+            None => log::warn!("Unsupported image format: {}", name),
+        }
+    }
+
+    let mut widths: Vec<usize> = config
+        .widths
+        .iter()
+        .map(|w| *w as usize)
+        .filter(|w| *w < width)
+        .collect();
+    widths.sort_unstable();
+    widths.dedup();
+
+    let mut decoded: Option<DynamicImage> = None;
+    let mut variants = Vec::new();
+
+    for format in formats {
+        let ext = format.extensions_str()[0];
+        let mut targets: Vec<(usize, String)> = widths
+            .iter()
+            .map(|w| (*w, format!("{}.{}w.{}", base, w, ext)))
+            .collect();
+        if format != original_format {
+            targets.push((width, format!("{}.{}", base, ext)));
+        }
+
+        for (target_width, file_name) in targets {
+            let target_height = scaled_height(width, height, target_width);
+            let target = dir.join(&file_name);
+
+            let size = if target.exists() {
+                output.keep(&target);
+                fs::metadata(&target).with_path(&target)?.len()
+            } else {
+                if decoded.is_none() {
+                    decoded = Some(
+                        image::load_from_memory_with_format(content, original_format)
+                            .map_err(|e| image_error(image_path, e))?,
+                    );
+                }
+                let source = decoded.as_ref().expect("decoded above");
+                let resized = source.resize_exact(
+                    target_width as u32,
+                    target_height as u32,
+                    FilterType::Lanczos3,
+                );
+                let mut encoded = Vec::new();
+                encodable(resized, format)
+                    .write_to(&mut Cursor::new(&mut encoded), format)
+                    .map_err(|e| image_error(&target, e))?;
+                output.write(&target, &encoded, false)?;
+                encoded.len() as u64
+            };
+
+            variants.push(ImageVariant {
+                path: format!("{}/{}", path_prefix.trim_end_matches('/'), file_name),
+                format: format.to_mime_type().to_string(),
+                width: target_width,
+                height: target_height,
+                size,
+            });
+        }
+    }
+
+    Ok(variants)
+}
+
+/// Decoding and encoding failures count as invalid data in the file.
+fn image_error(path: &Path, error: ImageError) -> ZoneError {
+    let source = match error {
+        ImageError::IoError(source) => source,
+        error => io::Error::new(io::ErrorKind::InvalidData, error),
+    };
+    ZoneError::Io {
+        path: path.to_path_buf(),
+        source,
+    }
+}
+
+fn variant_format(name: &str) -> Option<ImageFormat> {
+    match name {
+        "webp" => Some(ImageFormat::WebP),
+        "avif" if cfg!(feature = "avif") => Some(ImageFormat::Avif),
+        "png" => Some(ImageFormat::Png),
+        "jpeg" | "jpg" => Some(ImageFormat::Jpeg),
+        _ => None,
+    }
+}
+
+fn scaled_height(width: usize, height: usize, target_width: usize) -> usize {
+    if width == 0 {
+        return height;
+    }
+    ((height * target_width + width / 2) / width).max(1)
+}
+
+/// Converts the pixel layout to one the target encoder accepts.
+fn encodable(image: DynamicImage, format: ImageFormat) -> DynamicImage {
+    match format {
+        ImageFormat::Jpeg => DynamicImage::ImageRgb8(image.to_rgb8()),
+        ImageFormat::WebP | ImageFormat::Avif => DynamicImage::ImageRgba8(image.to_rgba8()),
+        _ => image,
+    }
+}
+
+/// Renders `<picture>` markup for an image, with one `<source>` per modern
+/// format and a `srcset` on the fallback `<img>`.
+pub fn picture_html(image: &ImageInfo, alt: &str, prefix: &str) -> String {
+    let fallback_format = image
+        .variants
+        .iter()
+        .find(|v| Path::new(&v.path).extension() == Path::new(&image.path).extension())
+        .map(|v| v.format.clone());
+
+    let mut sources: Vec<(String, Vec<String>)> = Vec::new();
+    let mut fallback_set = Vec::new();
+
+    for variant in &image.variants {
+        let candidate = format!("{}{} {}w", prefix, variant.path, variant.width);
+        if Some(&variant.format) == fallback_format.as_ref() {
+            fallback_set.push(candidate);
+        } else if let Some((_, set)) = sources.iter_mut().find(|(f, _)| *f == variant.format) {
+            set.push(candidate);
+        } else {
+            sources.push((variant.format.clone(), vec![candidate]));
+        }
+    }
+
+    let mut img = format!("<img src=\"{}{}\"", prefix, escape_attr(&image.path));
+    if !fallback_set.is_empty() {
+        if let Some(width) = image.width {
+            fallback_set.push(format!("{}{} {}w", prefix, image.path, width));
+        }
+        img.push_str(&format!(
+            " srcset=\"{}\" sizes=\"{}\"",
+            escape_attr(&fallback_set.join(", ")),
+            sizes_attr(image)
+        ));
+    }
+    if let (Some(width), Some(height)) = (image.width, image.height) {
+        img.push_str(&format!(" width=\"{}\" height=\"{}\"", width, height));
+    }
+    img.push_str(&format!(" alt=\"{}\" loading=\"lazy\">", escape_attr(alt)));
+
+    if sources.is_empty() {
+        return img;
+    }
+
+    let mut picture = String::from("<picture>");
+    for (format, set) in sources {
+        picture.push_str(&format!(
+            "<source type=\"{}\" srcset=\"{}\" sizes=\"{}\">",
+            format,
+            escape_attr(&set.join(", ")),
+            sizes_attr(image)
+        ));
+    }
+    picture.push_str(&img);
+    picture.push_str("</picture>");
+    picture
+}
+
+fn sizes_attr(image: &ImageInfo) -> String {
+    match image.width {
+        Some(width) => format!("(max-width: {}px) 100vw, {}px", width, width),
+        None => "100vw".to_string(),
+    }
+}
+
+/// Renders a Markdown body to HTML, emitting `<picture>` markup for every
+/// image found in `images` and plain `<img>` tags for the rest.
+pub fn render_html(body: &str, images: &[ImageInfo], prefix: &str) -> String {
+    let mut events = Vec::new();
+    let mut current: Option<(&ImageInfo, String)> = None;
+
+    for event in Parser::new(body) {
+        match event {
+            Event::Start(Tag::Image { ref dest_url, .. })
+                if current.is_none() && images.iter().any(|i| i.src == dest_url.as_ref()) =>
+            {
+                let image = images
+                    .iter()
+                    .find(|i| i.src == dest_url.as_ref())
+                    .expect("checked above");
+                current = Some((image, String::new()));
+            }
+            Event::End(TagEnd::Image) if current.is_some() => {
+                let (image, alt) = current.take().expect("checked above");
+                events.push(Event::InlineHtml(picture_html(image, &alt, prefix).into()));
+            }
+            Event::Text(text) | Event::Code(text) if current.is_some() => {
+                if let Some((_, alt)) = current.as_mut() {
+                    alt.push_str(&text);
+                }
+            }
+            _ if current.is_some() => {}
+            other => events.push(other),
+        }
+    }
+
+    let mut output = String::new();
+    html::push_html(&mut output, events.into_iter());
+    output
+}
+
+fn escape_attr(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
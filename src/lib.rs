@@ -1,7 +1,33 @@
 pub mod article;
-pub mod processor;
+pub mod assets;
+pub mod authors;
+pub mod cache;
+pub mod check;
+pub mod config;
+pub mod error;
+pub mod history;
+pub mod i18n;
+pub mod images;
+pub mod links;
+pub mod lint;
+pub mod logging;
 pub mod models;
+pub mod navigation;
+pub mod output;
+pub mod processor;
+pub mod report;
+pub mod scaffold;
+pub mod series;
+pub mod serve;
+pub mod touch;
+pub mod watch;
 
 pub use article::*;
+pub use check::{ChangeKind, CheckReport, FileChange};
+pub use config::*;
+pub use error::ZoneError;
+pub use lint::{Diagnostic, Linter, Severity};
+pub use models::*;
+pub use output::{OutputWriter, WriteSummary};
 pub use processor::*;
-pub use models::*;
\ No newline at end of file
+pub use report::BuildReport;
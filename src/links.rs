@@ -0,0 +1,281 @@
+use crate::Article;
+use crate::error::{Result, WithPath};
+use crate::lint::{self, Diagnostic, Severity};
+use crate::processor::relative_to;
+use crate::scaffold::slugify;
+use pulldown_cmark::{Event, LinkType, Parser, Tag, TagEnd};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Rule id of broken link diagnostics; `lint_ignore` accepts it like any
+/// lint rule.
+pub const BROKEN_LINK: &str = "broken-link";
+
+/// A link or image pointing outside the blog. These are listed but never
+/// fetched.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExternalLink {
+    /// Path relative to the blog root.
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub url: String,
+}
+
+#[derive(Debug, Default)]
+pub struct LinkReport {
+    /// Links and images whose target or anchor does not exist.
+    pub broken: Vec<Diagnostic>,
+    pub external: Vec<ExternalLink>,
+}
+
+/// A link or image found in an article body, located by byte offset in the
+/// whole file.
+struct Reference {
+    offset: usize,
+    dest: String,
+    image: bool,
+}
+
+struct Page {
+    file: String,
+    content: String,
+    references: Vec<Reference>,
+    anchors: BTreeSet<String>,
+    ignored: Vec<String>,
+}
+
+/// Checks every link and image in the bodies of `sources` against the blog
+/// tree. Relative links resolve against the article's directory and links
+/// starting with `/` against the blog root. A link to an article may name its
+/// Markdown file, its rendered `.html`, or either without an extension, and
+/// may use the article's `path` in place of its file name. Fragments on
+/// links to articles must match a heading anchor: the heading text as
+/// [`slugify`] turns it into a file name.
+pub fn check_links(blog_dir: &Path, sources: &[(PathBuf, Article)]) -> Result<LinkReport> {
+    let mut pages = Vec::new();
+    let mut targets: BTreeMap<String, usize> = BTreeMap::new();
+
+    for (path, article) in sources {
+        let file = relative_to(blog_dir, path);
+        let content = fs::read_to_string(path).with_path(path)?;
+        let Some((frontmatter, body)) = Article::split_frontmatter(&content) else {
+            continue;
+        };
+        let body_start = body.as_ptr() as usize - content.as_ptr() as usize;
+        let (references, anchors) = scan_body(body, body_start);
+        let ignored = lint::ignored_rules(frontmatter);
+
+        let index = pages.len();
+        let dir = file.rsplit_once('/').map(|(dir, _)| dir);
+        let stem = file.trim_end_matches(".md");
+        let alias = match dir {
+            Some(dir) => format!("{}/{}", dir, article.path),
+            None => article.path.clone(),
+        };
+        let alias_stem = alias.trim_end_matches(".md").to_string();
+        for key in [
+            file.clone(),
+            stem.to_string(),
+            format!("{}.html", stem),
+            alias,
+            format!("{}.html", alias_stem),
+            alias_stem,
+        ] {
+            targets.entry(key).or_insert(index);
+        }
+
+        pages.push(Page {
+            file,
+            content,
+            references,
+            anchors,
+            ignored,
+        });
+    }
+
+    let mut report = LinkReport::default();
+    for page in &pages {
+        if page
+            .ignored
+            .iter()
+            .any(|id| id == "all" || id == BROKEN_LINK)
+        {
+            continue;
+        }
+
+        for reference in &page.references {
+            let (line, column) = lint::line_column(&page.content, reference.offset);
+            if is_external(&reference.dest) {
+                report.external.push(ExternalLink {
+                    file: page.file.clone(),
+                    line,
+                    column,
+                    url: reference.dest.clone(),
+                });
+                continue;
+            }
+
+            if let Some(problem) = check_reference(blog_dir, page, reference, &pages, &targets) {
+                report.broken.push(Diagnostic {
+                    rule: BROKEN_LINK.to_string(),
+                    severity: Severity::Error,
+                    file: page.file.clone(),
+                    line: Some(line),
+                    column: Some(column),
+                    message: problem,
+                });
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Returns what is wrong with a local reference, if anything.
+fn check_reference(
+    blog_dir: &Path,
+    page: &Page,
+    reference: &Reference,
+    pages: &[Page],
+    targets: &BTreeMap<String, usize>,
+) -> Option<String> {
+    let (path, fragment) = match reference.dest.split_once('#') {
+        Some((path, fragment)) => (path, Some(fragment)),
+        None => (reference.dest.as_str(), None),
+    };
+    let path = percent_decode(path.split('?').next().unwrap_or(path));
+
+    let target = if path.is_empty() {
+        Some(page)
+    } else {
+        let Some(resolved) = resolve(&page.file, &path) else {
+            return Some(format!(
+                "{} points outside the blog directory",
+                reference.dest
+            ));
+        };
+        match targets.get(resolved.trim_end_matches('/')) {
+            Some(&index) if !reference.image => Some(&pages[index]),
+            _ if blog_dir.join(&resolved).exists() => None,
+            _ if reference.image => return Some(format!("missing image {}", reference.dest)),
+            _ => return Some(format!("broken link {}", reference.dest)),
+        }
+    };
+
+    match (target, fragment) {
+        (Some(target), Some(fragment))
+            if !fragment.is_empty() && !target.anchors.contains(&percent_decode(fragment)) =>
+        {
+            Some(format!("no heading #{} in {}", fragment, target.file))
+        }
+        _ => None,
+    }
+}
+
+/// Collects the links and images of a body together with the anchors of its
+/// headings. Offsets are shifted by `body_start` to index the whole file.
+fn scan_body(body: &str, body_start: usize) -> (Vec<Reference>, BTreeSet<String>) {
+    let mut references = Vec::new();
+    let mut anchors = BTreeSet::new();
+    let mut heading: Option<String> = None;
+
+    for (event, range) in Parser::new(body).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Link {
+                link_type,
+                dest_url,
+                ..
+            }) if link_type != LinkType::Email => references.push(Reference {
+                offset: body_start + range.start,
+                dest: dest_url.to_string(),
+                image: false,
+            }),
+            Event::Start(Tag::Image { dest_url, .. }) => references.push(Reference {
+                offset: body_start + range.start,
+                dest: dest_url.to_string(),
+                image: true,
+            }),
+            Event::Start(Tag::Heading { .. }) => heading = Some(String::new()),
+            Event::Text(text) | Event::Code(text) => {
+                if let Some(heading) = heading.as_mut() {
+                    heading.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                if let Some(text) = heading.take() {
+                    anchors.insert(slugify(&text));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    (references, anchors)
+}
+
+/// URLs with a scheme (`https:`, `mailto:`, ...) or protocol-relative ones.
+fn is_external(dest: &str) -> bool {
+    if dest.starts_with("//") {
+        return true;
+    }
+    match dest.find(':') {
+        Some(colon) => !dest[..colon].contains(['/', '?', '#']),
+        None => false,
+    }
+}
+
+/// Resolves a link path against the linking file, both relative to the blog
+/// root, into a `/`-separated path. Returns `None` when `..` leaves the root.
+fn resolve(file: &str, path: &str) -> Option<String> {
+    let mut segments: Vec<&str> = match path.strip_prefix('/') {
+        Some(_) => Vec::new(),
+        None => file.split('/').collect(),
+    };
+    if !path.starts_with('/') {
+        segments.pop();
+    }
+
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop()?;
+            }
+            segment => segments.push(segment),
+        }
+    }
+
+    let mut resolved = segments.join("/");
+    if path.ends_with('/') && !resolved.is_empty() {
+        resolved.push('/');
+    }
+    Some(resolved)
+}
+
+/// Decodes `%XX` escapes, leaving malformed ones as they are.
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let hex = s
+            .get(i + 1..i + 3)
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
@@ -0,0 +1,666 @@
+use crate::Article;
+use crate::config::LintConfig;
+use crate::error::{Result, ZoneError};
+use pulldown_cmark::{Event, HeadingLevel, Parser, Tag};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+/// Frontmatter key listing the rule ids to skip for one article; `"all"`
+/// skips every rule.
+pub const IGNORE_KEY: &str = "lint_ignore";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Off,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Off => "off",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+/// A problem found in a source file. Lines and columns start at 1; columns
+/// count characters.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    pub rule: String,
+    pub severity: Severity,
+    /// Path relative to the blog root.
+    pub file: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.file)?;
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+            if let Some(column) = self.column {
+                write!(f, ":{}", column)?;
+            }
+        }
+        write!(f, ": {}[{}]: {}", self.severity, self.rule, self.message)
+    }
+}
+
+/// The options of one rule, as configured in `zone.toml`.
+#[derive(Debug, Default, Clone)]
+pub struct RuleOptions {
+    table: toml::Table,
+}
+
+impl RuleOptions {
+    pub fn new(table: toml::Table) -> Self {
+        Self { table }
+    }
+
+    pub fn usize(&self, key: &str, default: usize) -> usize {
+        self.table
+            .get(key)
+            .and_then(|v| v.as_integer())
+            .and_then(|v| usize::try_from(v).ok())
+            .unwrap_or(default)
+    }
+
+    pub fn bool(&self, key: &str, default: bool) -> bool {
+        self.table
+            .get(key)
+            .and_then(|v| v.as_bool())
+            .unwrap_or(default)
+    }
+
+    pub fn strings(&self, key: &str) -> Vec<String> {
+        self.table
+            .get(key)
+            .and_then(|v| v.as_array())
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|v| v.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// A source file as seen by the rules, with the article as the build
+/// resolved it.
+pub struct Document<'a> {
+    /// Path relative to the blog root.
+    pub file: &'a str,
+    pub article: &'a Article,
+    pub content: &'a str,
+    pub frontmatter: &'a str,
+    pub body: &'a str,
+    /// Today's date as `YYYY-MM-DD`.
+    pub today: &'a str,
+}
+
+impl<'a> Document<'a> {
+    /// Returns `None` when the content has no frontmatter.
+    pub fn new(
+        file: &'a str,
+        article: &'a Article,
+        content: &'a str,
+        today: &'a str,
+    ) -> Option<Self> {
+        let (frontmatter, body) = Article::split_frontmatter(content)?;
+        Some(Self {
+            file,
+            article,
+            content,
+            frontmatter,
+            body,
+            today,
+        })
+    }
+
+    /// Byte offset of a slice of `content` within it.
+    pub fn offset_of(&self, part: &str) -> usize {
+        part.as_ptr() as usize - self.content.as_ptr() as usize
+    }
+
+    /// The 1-based line and character column of a byte offset in `content`.
+    pub fn line_column(&self, offset: usize) -> (usize, usize) {
+        line_column(self.content, offset)
+    }
+
+    /// Byte offset of a top-level frontmatter key, for pointing at a field.
+    pub fn key_offset(&self, key: &str) -> Option<usize> {
+        let mut offset = self.offset_of(self.frontmatter);
+        for line in self.frontmatter.split_inclusive('\n') {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with('[') {
+                return None;
+            }
+            if let Some(rest) = trimmed.strip_prefix(key)
+                && rest.trim_start().starts_with('=')
+            {
+                return Some(offset + line.len() - trimmed.len());
+            }
+            offset += line.len();
+        }
+        None
+    }
+
+    /// Each line of the body with its byte offset in `content`, skipping the
+    /// contents of fenced code blocks.
+    pub fn prose_lines(&self) -> Vec<(usize, &'a str)> {
+        let mut lines = Vec::new();
+        let mut fence: Option<&str> = None;
+        let mut offset = self.offset_of(self.body);
+
+        for line in self.body.split_inclusive('\n') {
+            let text = line.trim_end_matches(['\n', '\r']);
+            let trimmed = text.trim_start();
+            let marker = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m));
+
+            match (fence, marker) {
+                (None, Some(marker)) => fence = Some(marker),
+                (Some(open), Some(marker)) if open == marker => fence = None,
+                (None, None) => lines.push((offset, text)),
+                _ => {}
+            }
+            offset += line.len();
+        }
+
+        lines
+    }
+}
+
+/// A problem reported by a rule, located by byte offset in the content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub offset: Option<usize>,
+    pub message: String,
+}
+
+impl Finding {
+    pub fn new(offset: Option<usize>, message: impl Into<String>) -> Self {
+        Self {
+            offset,
+            message: message.into(),
+        }
+    }
+}
+
+/// A lint rule. Implement this and add it with [`Linter::register`] to
+/// extend the built-in checks.
+pub trait Rule: Send + Sync {
+    /// Kebab-case id used in `zone.toml`, suppressions and reports.
+    fn id(&self) -> &'static str;
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, doc: &Document, options: &RuleOptions) -> Vec<Finding>;
+}
+
+/// Runs a set of rules over documents.
+pub struct Linter {
+    rules: Vec<Box<dyn Rule>>,
+}
+
+impl Default for Linter {
+    /// The built-in rules.
+    fn default() -> Self {
+        Self {
+            rules: vec![
+                Box::new(SummaryLength),
+                Box::new(TitleCase),
+                Box::new(MissingCover),
+                Box::new(FutureDate),
+                Box::new(UpdateBeforeDate),
+                Box::new(TrailingWhitespace),
+                Box::new(HeadingHierarchy),
+                Box::new(LineLength),
+                Box::new(DisallowedWords),
+            ],
+        }
+    }
+}
+
+impl fmt::Debug for Linter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.rules.iter().map(|r| r.id()))
+            .finish()
+    }
+}
+
+impl Linter {
+    /// Adds a rule, replacing any rule with the same id.
+    pub fn register(&mut self, rule: impl Rule + 'static) {
+        self.rules.retain(|r| r.id() != rule.id());
+        self.rules.push(Box::new(rule));
+    }
+
+    pub fn rule_ids(&self) -> Vec<&'static str> {
+        self.rules.iter().map(|r| r.id()).collect()
+    }
+
+    /// Resolves every rule's severity and options, failing on configuration
+    /// for unknown rules or invalid severities. Errors are reported against
+    /// `config_path`, the file the `[lint]` section was read from.
+    pub fn configure(
+        &self,
+        config: &LintConfig,
+        config_path: &Path,
+    ) -> Result<Vec<(&dyn Rule, Severity, RuleOptions)>> {
+        for id in config.rules.keys() {
+            if !self.rules.iter().any(|r| r.id() == id) {
+                return Err(ZoneError::config(
+                    config_path,
+                    format!("unknown lint rule '{}'", id),
+                ));
+            }
+        }
+
+        self.rules
+            .iter()
+            .map(|rule| {
+                let (severity, table) = match config.rules.get(rule.id()) {
+                    None => (None, toml::Table::new()),
+                    Some(toml::Value::String(severity)) => {
+                        (Some(severity.clone()), toml::Table::new())
+                    }
+                    Some(toml::Value::Table(table)) => {
+                        let mut table = table.clone();
+                        let severity = table
+                            .remove("severity")
+                            .map(|v| v.as_str().map(str::to_string).unwrap_or_default());
+                        (severity, table)
+                    }
+                    Some(_) => {
+                        return Err(ZoneError::config(
+                            config_path,
+                            format!("invalid configuration for lint rule '{}'", rule.id()),
+                        ));
+                    }
+                };
+
+                let severity = match severity.as_deref() {
+                    None => rule.default_severity(),
+                    Some("off") => Severity::Off,
+                    Some("warning") => Severity::Warning,
+                    Some("error") => Severity::Error,
+                    Some(other) => {
+                        return Err(ZoneError::config(
+                            config_path,
+                            format!("invalid severity '{}' for lint rule '{}'", other, rule.id()),
+                        ));
+                    }
+                };
+
+                Ok((rule.as_ref(), severity, RuleOptions::new(table)))
+            })
+            .collect()
+    }
+
+    /// Runs every enabled rule over a document, honouring its `lint_ignore`.
+    /// Configuration errors name `config_path`.
+    pub fn lint(
+        &self,
+        doc: &Document,
+        config: &LintConfig,
+        config_path: &Path,
+    ) -> Result<Vec<Diagnostic>> {
+        let ignored = ignored_rules(doc.frontmatter);
+        let mut diagnostics = Vec::new();
+
+        for (rule, severity, options) in self.configure(config, config_path)? {
+            if severity == Severity::Off || ignored.iter().any(|id| id == "all" || id == rule.id())
+            {
+                continue;
+            }
+
+            for finding in rule.check(doc, &options) {
+                let position = finding.offset.map(|offset| doc.line_column(offset));
+                diagnostics.push(Diagnostic {
+                    rule: rule.id().to_string(),
+                    severity,
+                    file: doc.file.to_string(),
+                    line: position.map(|(line, _)| line),
+                    column: position.map(|(_, column)| column),
+                    message: finding.message,
+                });
+            }
+        }
+
+        diagnostics.sort_by_key(|d| (d.line, d.column));
+        Ok(diagnostics)
+    }
+}
+
+/// The 1-based line and character column of a byte offset in `text`.
+pub fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset.min(text.len())];
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
+/// Reads `lint_ignore`, given as a list of rule ids or a single id.
+pub(crate) fn ignored_rules(frontmatter: &str) -> Vec<String> {
+    let Ok(table) = toml::from_str::<toml::Table>(frontmatter) else {
+        return Vec::new();
+    };
+
+    match table.get(IGNORE_KEY) {
+        Some(toml::Value::String(id)) => vec![id.clone()],
+        Some(toml::Value::Array(ids)) => ids
+            .iter()
+            .filter_map(|v| v.as_str().map(str::to_string))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn day(date: &str) -> &str {
+    date.get(..10).unwrap_or(date)
+}
+
+/// `min` and `max` bound the summary length in characters.
+struct SummaryLength;
+
+impl Rule for SummaryLength {
+    fn id(&self) -> &'static str {
+        "summary-length"
+    }
+
+    fn check(&self, doc: &Document, options: &RuleOptions) -> Vec<Finding> {
+        let (min, max) = (options.usize("min", 20), options.usize("max", 200));
+        let len = doc.article.summary.chars().count();
+        let offset = doc.key_offset("summary");
+
+        if len < min {
+            vec![Finding::new(
+                offset,
+                format!("summary has {} characters, fewer than {}", len, min),
+            )]
+        } else if len > max {
+            vec![Finding::new(
+                offset,
+                format!("summary has {} characters, more than {}", len, max),
+            )]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+/// Major words of Latin-script titles start with a capital letter; short
+/// articles, conjunctions and prepositions may stay lowercase inside the
+/// title.
+struct TitleCase;
+
+const MINOR_WORDS: &[&str] = &[
+    "a", "an", "and", "as", "at", "but", "by", "for", "from", "in", "into", "nor", "of", "on",
+    "or", "over", "the", "to", "vs", "via", "with",
+];
+
+impl Rule for TitleCase {
+    fn id(&self) -> &'static str {
+        "title-case"
+    }
+
+    fn check(&self, doc: &Document, _options: &RuleOptions) -> Vec<Finding> {
+        let words: Vec<&str> = doc.article.title.split_whitespace().collect();
+        let mut lowercase = Vec::new();
+
+        for (i, word) in words.iter().enumerate() {
+            let bare = word.trim_start_matches(|c: char| !c.is_alphanumeric());
+            let Some(first) = bare.chars().next() else {
+                continue;
+            };
+            let minor = MINOR_WORDS.contains(&bare.to_lowercase().as_str());
+            let edge = i == 0 || i == words.len() - 1;
+
+            if first.is_ascii_lowercase() && (edge || !minor) {
+                lowercase.push(format!("'{}'", bare));
+            }
+        }
+
+        if lowercase.is_empty() {
+            return Vec::new();
+        }
+        vec![Finding::new(
+            doc.key_offset("title"),
+            format!("title is not in title case: {}", lowercase.join(", ")),
+        )]
+    }
+}
+
+/// The article has no usable cover image, neither set explicitly nor taken
+/// from the first image in the body.
+struct MissingCover;
+
+impl Rule for MissingCover {
+    fn id(&self) -> &'static str {
+        "missing-cover"
+    }
+
+    fn check(&self, doc: &Document, _options: &RuleOptions) -> Vec<Finding> {
+        match doc.article.cover {
+            Some(_) => Vec::new(),
+            None => vec![Finding::new(
+                doc.key_offset("cover"),
+                "article has no cover image",
+            )],
+        }
+    }
+}
+
+struct FutureDate;
+
+impl Rule for FutureDate {
+    fn id(&self) -> &'static str {
+        "future-date"
+    }
+
+    fn check(&self, doc: &Document, _options: &RuleOptions) -> Vec<Finding> {
+        [("date", &doc.article.date), ("update", &doc.article.update)]
+            .into_iter()
+            .filter(|(_, value)| day(value) > doc.today)
+            .map(|(key, value)| {
+                Finding::new(
+                    doc.key_offset(key),
+                    format!("{} {} is in the future", key, value),
+                )
+            })
+            .collect()
+    }
+}
+
+struct UpdateBeforeDate;
+
+impl Rule for UpdateBeforeDate {
+    fn id(&self) -> &'static str {
+        "update-before-date"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, doc: &Document, _options: &RuleOptions) -> Vec<Finding> {
+        let article = doc.article;
+        if day(&article.update) >= day(&article.date) {
+            return Vec::new();
+        }
+        vec![Finding::new(
+            doc.key_offset("update"),
+            format!(
+                "update {} is earlier than date {}",
+                article.update, article.date
+            ),
+        )]
+    }
+}
+
+/// Lines ending in spaces or tabs. With `allow_hard_breaks` (the default),
+/// two or more trailing spaces after text in the body are accepted as a
+/// Markdown line break.
+struct TrailingWhitespace;
+
+impl Rule for TrailingWhitespace {
+    fn id(&self) -> &'static str {
+        "trailing-whitespace"
+    }
+
+    fn check(&self, doc: &Document, options: &RuleOptions) -> Vec<Finding> {
+        let allow_hard_breaks = options.bool("allow_hard_breaks", true);
+        let body_start = doc.offset_of(doc.body);
+        let mut findings = Vec::new();
+        let mut offset = 0;
+
+        for line in doc.content.split_inclusive('\n') {
+            let text = line.trim_end_matches(['\n', '\r']);
+            let trimmed = text.trim_end_matches([' ', '\t']);
+            let trailing = &text[trimmed.len()..];
+
+            let hard_break = allow_hard_breaks
+                && offset >= body_start
+                && !trimmed.trim().is_empty()
+                && trailing.len() >= 2
+                && trailing.bytes().all(|b| b == b' ');
+
+            if !trailing.is_empty() && !hard_break {
+                findings.push(Finding::new(
+                    Some(offset + trimmed.len()),
+                    "trailing whitespace",
+                ));
+            }
+            offset += line.len();
+        }
+
+        findings
+    }
+}
+
+/// Headings may go at most one level deeper than the previous heading.
+struct HeadingHierarchy;
+
+impl Rule for HeadingHierarchy {
+    fn id(&self) -> &'static str {
+        "heading-hierarchy"
+    }
+
+    fn check(&self, doc: &Document, _options: &RuleOptions) -> Vec<Finding> {
+        let body_start = doc.offset_of(doc.body);
+        let mut previous: Option<usize> = None;
+        let mut findings = Vec::new();
+
+        for (event, range) in Parser::new(doc.body).into_offset_iter() {
+            if let Event::Start(Tag::Heading { level, .. }) = event {
+                let level = heading_number(level);
+                if let Some(previous) = previous
+                    && level > previous + 1
+                {
+                    findings.push(Finding::new(
+                        Some(body_start + range.start),
+                        format!("heading jumps from h{} to h{}", previous, level),
+                    ));
+                }
+                previous = Some(level);
+            }
+        }
+
+        findings
+    }
+}
+
+fn heading_number(level: HeadingLevel) -> usize {
+    match level {
+        HeadingLevel::H1 => 1,
+        HeadingLevel::H2 => 2,
+        HeadingLevel::H3 => 3,
+        HeadingLevel::H4 => 4,
+        HeadingLevel::H5 => 5,
+        HeadingLevel::H6 => 6,
+    }
+}
+
+/// Body lines longer than `max` characters (default 120). Code blocks and
+/// lines without spaces, such as long URLs, are skipped.
+struct LineLength;
+
+impl Rule for LineLength {
+    fn id(&self) -> &'static str {
+        "line-length"
+    }
+
+    fn check(&self, doc: &Document, options: &RuleOptions) -> Vec<Finding> {
+        let max = options.usize("max", 120);
+
+        doc.prose_lines()
+            .into_iter()
+            .filter(|(_, line)| line.trim().contains(' '))
+            .filter_map(|(offset, line)| {
+                let len = line.chars().count();
+                (len > max).then(|| {
+                    Finding::new(
+                        Some(offset),
+                        format!("line has {} characters, more than {}", len, max),
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+/// Words or phrases listed in `words`, matched as whole words regardless of
+/// ASCII case anywhere in the file.
+struct DisallowedWords;
+
+impl Rule for DisallowedWords {
+    fn id(&self) -> &'static str {
+        "disallowed-words"
+    }
+
+    fn check(&self, doc: &Document, options: &RuleOptions) -> Vec<Finding> {
+        // ASCII lowercasing keeps every byte offset in place.
+        let haystack = doc.content.to_ascii_lowercase();
+        let mut findings = Vec::new();
+
+        for word in options.strings("words") {
+            let needle = word.to_ascii_lowercase();
+            if needle.is_empty() {
+                continue;
+            }
+
+            for (start, _) in haystack.match_indices(&needle) {
+                let end = start + needle.len();
+                let before = haystack[..start].chars().next_back();
+                let after = haystack[end..].chars().next();
+                if !before.is_some_and(char::is_alphanumeric)
+                    && !after.is_some_and(char::is_alphanumeric)
+                {
+                    findings.push(Finding::new(
+                        Some(start),
+                        format!("'{}' is not allowed", &doc.content[start..end]),
+                    ));
+                }
+            }
+        }
+
+        findings.sort_by_key(|f| f.offset);
+        findings
+    }
+}
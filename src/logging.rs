@@ -0,0 +1,98 @@
+use log::kv::{self, Key, Value, VisitSource};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::io::Write;
+
+/// How [`init`] renders log events on stderr.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// The message alone, with warnings and errors prefixed by their level.
+    /// Key-value fields are left out; messages carry the same details.
+    #[default]
+    Text,
+    /// One JSON object per line with `timestamp`, `level`, `target`,
+    /// `message` and every key-value field.
+    Json,
+}
+
+/// Installs a logger writing events from this crate up to `level` to
+/// stderr. The library never installs one itself, so embedding it stays
+/// silent unless the host sets up logging. Fails when a logger is already
+/// installed.
+pub fn init(level: LevelFilter, format: LogFormat) -> Result<(), log::SetLoggerError> {
+    log::set_boxed_logger(Box::new(Logger { format }))?;
+    log::set_max_level(level);
+    Ok(())
+}
+
+struct Logger {
+    format: LogFormat,
+}
+
+impl Log for Logger {
+    /// Other crates only get through with warnings and errors.
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Warn || metadata.target().starts_with(env!("CARGO_CRATE_NAME"))
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            let line = format_record(record, self.format);
+            let _ = writeln!(std::io::stderr().lock(), "{}", line);
+        }
+    }
+
+    fn flush(&self) {
+        let _ = std::io::stderr().flush();
+    }
+}
+
+/// Renders one event as a line in `format`.
+pub fn format_record(record: &Record, format: LogFormat) -> String {
+    match format {
+        LogFormat::Text => match record.level() {
+            Level::Error => format!("error: {}", record.args()),
+            Level::Warn => format!("warning: {}", record.args()),
+            _ => record.args().to_string(),
+        },
+        LogFormat::Json => {
+            let mut fields = Fields::default();
+            let _ = record.key_values().visit(&mut fields);
+
+            let mut event = serde_json::Map::new();
+            event.insert(
+                "timestamp".to_string(),
+                chrono::Local::now().to_rfc3339().into(),
+            );
+            event.insert(
+                "level".to_string(),
+                record.level().as_str().to_lowercase().into(),
+            );
+            event.insert("target".to_string(), record.target().into());
+            event.insert("message".to_string(), record.args().to_string().into());
+            for (key, value) in fields.0 {
+                event.insert(key, value);
+            }
+            serde_json::Value::Object(event).to_string()
+        }
+    }
+}
+
+/// Key-value fields of an event, in the order they were given.
+#[derive(Default)]
+struct Fields(Vec<(String, serde_json::Value)>);
+
+impl<'kvs> VisitSource<'kvs> for Fields {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error> {
+        let value = if let Some(b) = value.to_bool() {
+            b.into()
+        } else if let Some(n) = value.to_u64() {
+            n.into()
+        } else if let Some(n) = value.to_i64() {
+            n.into()
+        } else {
+            value.to_string().into()
+        };
+        self.0.push((key.to_string(), value));
+        Ok(())
+    }
+}
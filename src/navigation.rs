@@ -0,0 +1,54 @@
+use crate::Article;
+use crate::models::ArticleLink;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+/// Orders articles newest first. Articles published on the same date are
+/// ordered by path so every run produces the same listing.
+pub fn newest_first(a: &Article, b: &Article) -> Ordering {
+    b.date.cmp(&a.date).then_with(|| a.path.cmp(&b.path))
+}
+
+pub fn sort_newest_first(articles: &mut [Article]) {
+    articles.sort_by(newest_first);
+}
+
+/// Links every article to the previous (older) and next (newer) article of
+/// its own collection in the same language.
+pub fn attach_collection_links(articles: &mut [Article]) {
+    let mut by_collection: BTreeMap<(Option<String>, Option<String>), Vec<usize>> = BTreeMap::new();
+    for (idx, article) in articles.iter().enumerate() {
+        by_collection
+            .entry((article.collection.clone(), article.lang.clone()))
+            .or_default()
+            .push(idx);
+    }
+
+    for ((collection, _), mut members) in by_collection {
+        if collection.is_none() {
+            continue;
+        }
+
+        members.sort_by(|a, b| newest_first(&articles[*a], &articles[*b]));
+        let links: Vec<ArticleLink> = members
+            .iter()
+            .map(|idx| ArticleLink::from(&articles[*idx]))
+            .collect();
+
+        for (position, idx) in members.iter().enumerate() {
+            articles[*idx].prev = links.get(position + 1).cloned();
+            articles[*idx].next = position.checked_sub(1).map(|p| links[p].clone());
+        }
+    }
+}
+
+/// Links every article of an already sorted, newest-first listing to its
+/// neighbours across the whole site.
+pub fn attach_site_links(sorted_articles: &mut [Article]) {
+    let links: Vec<ArticleLink> = sorted_articles.iter().map(ArticleLink::from).collect();
+
+    for (position, article) in sorted_articles.iter_mut().enumerate() {
+        article.site_prev = links.get(position + 1).cloned();
+        article.site_next = position.checked_sub(1).map(|p| links[p].clone());
+    }
+}
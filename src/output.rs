@@ -0,0 +1,280 @@
+use crate::error::{Result, WithPath};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Writes generated files, leaving a file untouched when it already holds
+/// exactly the new contents, and counts both outcomes.
+///
+/// Every write goes to a temporary file in the target's directory that is
+/// synced and then renamed over the target, so readers never see a truncated
+/// file. Between [`begin`](Self::begin) and [`commit`](Self::commit) the
+/// renames are deferred, so a build that fails before the commit leaves every
+/// previous output in place. The commit renames one file at a time, so a
+/// rename failing there leaves the earlier ones already replaced. After [`begin_dry_run`](Self::begin_dry_run) nothing is written at
+/// all; the new contents of every file that would change are kept in memory
+/// instead.
+#[derive(Debug, Default)]
+pub struct OutputWriter {
+    written: AtomicUsize,
+    unchanged: AtomicUsize,
+    dry_run: AtomicBool,
+    staged: Mutex<Option<Vec<StagedFile>>>,
+    planned: Mutex<Vec<PlannedWrite>>,
+    outputs: Mutex<BTreeMap<PathBuf, WriteOutcome>>,
+}
+
+/// A file a dry run would create or change, with its new contents.
+#[derive(Debug, Clone)]
+pub struct PlannedWrite {
+    pub path: PathBuf,
+    pub contents: Vec<u8>,
+}
+
+#[derive(Debug)]
+struct StagedFile {
+    temp: PathBuf,
+    target: PathBuf,
+}
+
+/// What happened to one output file during a build.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WriteOutcome {
+    /// New contents were written, or would have been in a dry run.
+    Written,
+    /// The file already held exactly the new contents.
+    Unchanged,
+    /// Reused as it was on disk and only recorded, e.g. an existing image
+    /// variant.
+    Kept,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct WriteSummary {
+    pub written: usize,
+    pub unchanged: usize,
+}
+
+impl OutputWriter {
+    /// Writes `contents` to `path` unless the file is already identical.
+    /// With `force` the comparison is skipped. Returns whether the file was
+    /// written, or would have been in a dry run. Missing parent directories
+    /// are created.
+    pub fn write(&self, path: &Path, contents: &[u8], force: bool) -> Result<bool> {
+        self.keep(path);
+        let dry_run = self.is_dry_run();
+
+        if (!force || dry_run) && fs::read(path).is_ok_and(|existing| existing == contents) {
+            self.unchanged.fetch_add(1, Ordering::Relaxed);
+            self.note(path, WriteOutcome::Unchanged);
+            return Ok(false);
+        }
+
+        if dry_run {
+            let mut planned = self.planned.lock().expect("output writer lock poisoned");
+            planned.retain(|p| p.path != path);
+            planned.push(PlannedWrite {
+                path: path.to_path_buf(),
+                contents: contents.to_vec(),
+            });
+            self.written.fetch_add(1, Ordering::Relaxed);
+            self.note(path, WriteOutcome::Written);
+            return Ok(true);
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).with_path(parent)?;
+        }
+
+        let mut staged = self.staged.lock().expect("output writer lock poisoned");
+        match staged.as_mut() {
+            Some(files) => {
+                let temp = write_temp(path, contents).with_path(path)?;
+                if !files.iter().any(|f| f.target == path) {
+                    files.push(StagedFile {
+                        temp,
+                        target: path.to_path_buf(),
+                    });
+                }
+            }
+            None => write_atomic(path, contents)?,
+        }
+
+        self.written.fetch_add(1, Ordering::Relaxed);
+        self.note(path, WriteOutcome::Written);
+        Ok(true)
+    }
+
+    /// Records `path` as an output of the current build without writing it,
+    /// for files that are reused as they are, such as existing image variants.
+    pub fn keep(&self, path: &Path) {
+        self.outputs
+            .lock()
+            .expect("output writer lock poisoned")
+            .entry(path.to_path_buf())
+            .or_insert(WriteOutcome::Kept);
+    }
+
+    /// Records what happened to an output of the current build.
+    pub fn note(&self, path: &Path, outcome: WriteOutcome) {
+        self.outputs
+            .lock()
+            .expect("output writer lock poisoned")
+            .insert(path.to_path_buf(), outcome);
+    }
+
+    /// Starts deferring renames until `commit`, and resets the counters.
+    pub fn begin(&self) {
+        self.written.store(0, Ordering::Relaxed);
+        self.unchanged.store(0, Ordering::Relaxed);
+        self.dry_run.store(false, Ordering::Relaxed);
+        self.planned
+            .lock()
+            .expect("output writer lock poisoned")
+            .clear();
+        self.outputs
+            .lock()
+            .expect("output writer lock poisoned")
+            .clear();
+        *self.staged.lock().expect("output writer lock poisoned") = Some(Vec::new());
+    }
+
+    /// Like `begin`, but nothing is written until `rollback`; the contents of
+    /// changed files are kept for [`planned`](Self::planned).
+    pub fn begin_dry_run(&self) {
+        self.begin();
+        self.dry_run.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run.load(Ordering::Relaxed)
+    }
+
+    /// Moves every staged file into place. A failed rename stops the commit
+    /// and discards the files not yet moved; those already moved stay.
+    pub fn commit(&self) -> Result<()> {
+        let files = self
+            .staged
+            .lock()
+            .expect("output writer lock poisoned")
+            .take()
+            .unwrap_or_default();
+
+        let mut remaining = files.iter();
+        for file in remaining.by_ref() {
+            if let Err(error) = fs::rename(&file.temp, &file.target) {
+                for rest in remaining {
+                    let _ = fs::remove_file(&rest.temp);
+                }
+                return Err(error).with_path(&file.target);
+            }
+            sync_parent(&file.target);
+        }
+
+        Ok(())
+    }
+
+    /// Discards every staged file, leaving the previous outputs untouched,
+    /// and ends a dry run.
+    pub fn rollback(&self) {
+        self.dry_run.store(false, Ordering::Relaxed);
+        let files = self
+            .staged
+            .lock()
+            .expect("output writer lock poisoned")
+            .take()
+            .unwrap_or_default();
+
+        for file in files {
+            let _ = fs::remove_file(&file.temp);
+        }
+    }
+
+    /// The files the last dry run would have created or changed.
+    pub fn planned(&self) -> Vec<PlannedWrite> {
+        self.planned
+            .lock()
+            .expect("output writer lock poisoned")
+            .clone()
+    }
+
+    /// Every file produced by the last build, whether written or not.
+    pub fn outputs(&self) -> BTreeSet<PathBuf> {
+        self.outputs
+            .lock()
+            .expect("output writer lock poisoned")
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    /// Every file produced by the last build with its outcome, sorted by path.
+    pub fn outcomes(&self) -> Vec<(PathBuf, WriteOutcome)> {
+        self.outputs
+            .lock()
+            .expect("output writer lock poisoned")
+            .iter()
+            .map(|(path, outcome)| (path.clone(), *outcome))
+            .collect()
+    }
+
+    pub fn summary(&self) -> WriteSummary {
+        WriteSummary {
+            written: self.written.load(Ordering::Relaxed),
+            unchanged: self.unchanged.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Replaces `path` with `contents` through a synced temporary file and a
+/// rename, so the file is either fully old or fully new.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    let temp = write_temp(path, contents).with_path(path)?;
+    if let Err(error) = fs::rename(&temp, path) {
+        let _ = fs::remove_file(&temp);
+        return Err(error).with_path(path);
+    }
+    sync_parent(path);
+    Ok(())
+}
+
+/// The temporary sibling used while writing `path`, named after it and the
+/// process ID, e.g. `.index.json.4242.tmp`.
+pub fn temp_path(path: &Path) -> PathBuf {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.{}.tmp", file_name, std::process::id()))
+}
+
+fn write_temp(path: &Path, contents: &[u8]) -> io::Result<PathBuf> {
+    let temp = temp_path(path);
+    let result = fs::File::create(&temp).and_then(|mut file| {
+        file.write_all(contents)?;
+        file.sync_all()
+    });
+
+    match result {
+        Ok(()) => Ok(temp),
+        Err(error) => {
+            let _ = fs::remove_file(&temp);
+            Err(error)
+        }
+    }
+}
+
+/// Persists the rename itself; not every platform can sync a directory, so
+/// failures are ignored.
+fn sync_parent(path: &Path) {
+    if let Some(parent) = path.parent()
+        && let Ok(dir) = fs::File::open(parent)
+    {
+        let _ = dir.sync_all();
+    }
+}
@@ -1,29 +1,77 @@
+use crate::cache::{BuildCache, CacheEntry};
+use crate::check::{ChangeKind, CheckReport, FileChange};
+use crate::config::CONFIG_FILE_NAME;
+use crate::error::{self, Result, WithPath};
 use crate::models::*;
-use crate::Article;
-use std::collections::HashMap;
+use crate::output::{OutputWriter, WriteSummary};
+use crate::report::{BuildReport, FileReport, OutputReport, Recorder};
+use crate::{
+    Article, Config, assets, authors, cache, history, i18n, images, links, lint, navigation,
+    scaffold, series,
+};
+use rayon::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
-use std::path::Path;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::time::Instant;
 use walkdir::WalkDir;
 
 #[derive(Debug)]
 pub struct BlogProcessor {
     pub blog_dir: String,
+    pub config: Config,
+    /// Ignores the build cache and rewrites every output file.
+    pub force: bool,
+    /// Number of threads used to parse Markdown files; 0 uses one per CPU.
+    pub jobs: usize,
+    /// Directory receiving the generated files; defaults to the blog directory.
+    pub output_dir: Option<PathBuf>,
+    /// Rules run by [`check`](Self::check); register custom rules here.
+    pub linter: lint::Linter,
+    output: OutputWriter,
+    recorder: Recorder,
+}
+
+/// What a build produced besides its staged outputs.
+struct Built {
+    cache: BuildCache,
+    articles: Vec<Article>,
+    /// Every built source file with its article, before links between
+    /// articles are attached.
+    sources: Vec<(PathBuf, Article)>,
 }
 
 impl BlogProcessor {
     pub fn new(blog_dir: String) -> Self {
-        Self { blog_dir }
+        Self::with_config(blog_dir, Config::default())
     }
 
-    pub fn update_category_meta(
-        &self,
-        category: &str,
-        article: &Article,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let meta_path = Path::new(&self.blog_dir).join(category).join("meta.json");
+    pub fn with_config(blog_dir: String, config: Config) -> Self {
+        Self {
+            blog_dir,
+            config,
+            force: false,
+            jobs: 0,
+            output_dir: None,
+            linter: lint::Linter::default(),
+            output: OutputWriter::default(),
+            recorder: Recorder::default(),
+        }
+    }
+
+    /// The directory generated files are written to.
+    pub fn output_root(&self) -> PathBuf {
+        self.output_dir
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(&self.blog_dir))
+    }
+
+    pub fn update_category_meta(&self, category: &str, article: &Article) -> Result<()> {
+        let meta_path = self.output_root().join(category).join("meta.json");
 
         let mut articles: Vec<Article> = if meta_path.exists() {
-            let content = fs::read_to_string(&meta_path)?;
+            let content = fs::read_to_string(&meta_path).with_path(&meta_path)?;
             serde_json::from_str(&content).unwrap_or_else(|_| Vec::new())
         } else {
             Vec::new()
@@ -46,24 +94,19 @@ impl BlogProcessor {
             articles.push(article.clone());
         }
 
-        articles.sort_by(|a, b| b.date.cmp(&a.date));
+        navigation::sort_newest_first(&mut articles);
 
-        let json_content = serde_json::to_string_pretty(&articles)?;
-        fs::write(&meta_path, json_content)?;
+        let json_content = serde_json::to_string_pretty(&articles).with_path(&meta_path)?;
+        self.write_output(&meta_path, json_content)?;
 
         Ok(())
     }
 
-    fn update_root_file(
-        &self,
-        filename: &str,
-        article: &Article,
-        max_count: usize,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let file_path = Path::new(&self.blog_dir).join(filename);
+    fn update_root_file(&self, filename: &str, article: &Article, max_count: usize) -> Result<()> {
+        let file_path = self.output_root().join(filename);
 
         let mut articles: Vec<Article> = if file_path.exists() {
-            let content = fs::read_to_string(&file_path)?;
+            let content = fs::read_to_string(&file_path).with_path(&file_path)?;
             serde_json::from_str(&content).unwrap_or_else(|_| Vec::new())
         } else {
             Vec::new()
@@ -86,24 +129,24 @@ impl BlogProcessor {
             articles.push(article.clone());
         }
 
-        articles.sort_by(|a, b| b.date.cmp(&a.date));
+        navigation::sort_newest_first(&mut articles);
 
         if max_count != usize::MAX && articles.len() > max_count {
             articles.truncate(max_count);
         }
 
-        let json_content = serde_json::to_string_pretty(&articles)?;
-        fs::write(&file_path, json_content)?;
+        let json_content = serde_json::to_string_pretty(&articles).with_path(&file_path)?;
+        self.write_output(&file_path, json_content)?;
 
         Ok(())
     }
 
-    fn update_index_json(&self, article: &Article) -> Result<(), Box<dyn std::error::Error>> {
-        let file_path = Path::new(&self.blog_dir).join("index.json");
+    fn update_index_json(&self, article: &Article) -> Result<()> {
+        let file_path = self.output_root().join("index.json");
 
         let mut index_data: IndexJson = if file_path.exists() {
-            let content = fs::read_to_string(&file_path)?;
-            serde_json::from_str(&content)?
+            let content = fs::read_to_string(&file_path).with_path(&file_path)?;
+            serde_json::from_str(&content).with_path(&file_path)?
         } else {
             IndexJson {
                 meta: MetaInfo {
@@ -130,72 +173,45 @@ impl BlogProcessor {
             index_data.list.push(article.clone());
         }
 
-        index_data.list.sort_by(|a, b| b.date.cmp(&a.date));
+        navigation::sort_newest_first(&mut index_data.list);
 
-        let json_content = serde_json::to_string_pretty(&index_data)?;
-        fs::write(&file_path, json_content)?;
+        let json_content = serde_json::to_string_pretty(&index_data).with_path(&file_path)?;
+        self.write_output(&file_path, json_content)?;
 
         Ok(())
     }
 
-    fn update_notebooks_json(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let file_path = Path::new(&self.blog_dir).join("notebooks.json");
+    fn update_notebooks_json(&self) -> Result<()> {
+        let file_path = self.output_root().join("notebooks.json");
 
-        let mut notebooks = Vec::new();
-        let blog_path = Path::new(&self.blog_dir);
+        let mut notebooks = collect_notebooks(Path::new(&self.blog_dir), "")?;
+        number_notebooks(&mut notebooks);
 
-        for entry in WalkDir::new(blog_path)
-            .min_depth(1)
-            .max_depth(1)
-            .into_iter()
-            .filter_map(|e| e.ok())
-        {
-            if entry.file_type().is_dir() {
-                let dir_name = entry.file_name().to_string_lossy();
-                if dir_name != "images" {
-                    let dir_path = entry.path();
-                    let has_md_files = dir_path
-                        .read_dir()
-                        .map(|entries| {
-                            entries.filter_map(|e| e.ok()).any(|entry| {
-                                entry
-                                    .path()
-                                    .extension()
-                                    .map(|ext| ext == "md")
-                                    .unwrap_or(false)
-                            })
-                        })
-                        .unwrap_or(false);
-
-                    if has_md_files {
-                        let path = dir_name.to_string();
-
-                        let title = {
-                            let name_txt_path = dir_path.join("name.txt");
-                            if name_txt_path.exists() {
-                                fs::read_to_string(&name_txt_path)?.trim().to_string()
-                            } else {
-                                capitalize_first(&path)
-                            }
-                        };
-
-                        let notebook = NotebookEntry {
-                            id: notebooks.len(),
-                            title,
-                            subtitle: path.to_uppercase(),
-                            remark: format!("{} articles", capitalize_first(&path)),
-                            path: path,
-                        };
-                        notebooks.push(notebook);
-                    }
-                }
+        let json_content = serde_json::to_string_pretty(&notebooks).with_path(&file_path)?;
+        self.write_output(&file_path, json_content)?;
+
+        Ok(())
+    }
+
+    /// Writes the `meta.json` of every collection level, or `meta.<lang>.json`
+    /// when a language is given.
+    fn update_collection_meta_files(&self, articles: &[Article], lang: Option<&str>) -> Result<()> {
+        let mut articles_by_category: BTreeMap<String, Vec<Article>> = BTreeMap::new();
+        for article in articles {
+            if let Some(ref category) = article.collection {
+                articles_by_category
+                    .entry(category.clone())
+                    .or_default()
+                    .push(article.clone());
             }
         }
 
-        notebooks.sort_by(|a, b| a.id.cmp(&b.id));
-
-        let json_content = serde_json::to_string_pretty(&notebooks)?;
-        fs::write(&file_path, json_content)?;
+        for (category, category_articles) in collection_levels(
+            &articles_by_category,
+            self.config.collections.include_descendants,
+        ) {
+            self.update_category_meta_from_list(&category, &category_articles, lang)?;
+        }
 
         Ok(())
     }
@@ -204,29 +220,60 @@ impl BlogProcessor {
         &self,
         category: &str,
         articles: &[Article],
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let meta_path = Path::new(&self.blog_dir).join(category).join("meta.json");
+        lang: Option<&str>,
+    ) -> Result<()> {
+        let meta_path = self
+            .output_root()
+            .join(category)
+            .join(i18n::localized_file_name("meta.json", lang));
 
         let mut sorted_articles = articles.to_vec();
-        sorted_articles.sort_by(|a, b| b.date.cmp(&a.date));
+        navigation::sort_newest_first(&mut sorted_articles);
+        self.filter_extra("meta.json", &mut sorted_articles);
 
-        let json_content = serde_json::to_string_pretty(&sorted_articles)?;
-        fs::write(&meta_path, json_content)?;
+        let json_content = serde_json::to_string_pretty(&sorted_articles).with_path(&meta_path)?;
+        self.write_output(&meta_path, json_content)?;
 
         Ok(())
     }
 
-    fn update_all_root_json_files(
-        &self,
-        all_articles: &[Article],
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        self.update_root_file_from_list("latest.json", all_articles, 5)?;
+    /// Drops the extra frontmatter keys that are not exported to `file_name`.
+    fn filter_extra(&self, file_name: &str, articles: &mut [Article]) {
+        for article in articles {
+            article
+                .extra
+                .retain(|key, _| self.config.extra.allows(file_name, key));
+        }
+    }
+
+    fn update_all_root_json_files(&self, all_articles: &[Article]) -> Result<()> {
+        self.update_root_file_from_list("latest.json", all_articles, 5, None)?;
 
-        self.update_root_file_from_list("recommended.json", all_articles, usize::MAX)?;
+        self.update_root_file_from_list("recommended.json", all_articles, usize::MAX, None)?;
 
         self.update_notebooks_json()?;
 
-        self.update_index_json_from_list(all_articles)?;
+        self.update_index_json_from_list(all_articles, None)?;
+
+        Ok(())
+    }
+
+    /// Writes the per-language variants of the collection and root listings,
+    /// each holding only the articles in that language.
+    fn update_language_files(&self, all_articles: &[Article]) -> Result<()> {
+        for lang in &self.config.i18n.languages {
+            let articles: Vec<Article> = all_articles
+                .iter()
+                .filter(|a| a.lang.as_deref() == Some(lang.as_str()))
+                .cloned()
+                .collect();
+            let lang = Some(lang.as_str());
+
+            self.update_collection_meta_files(&articles, lang)?;
+            self.update_root_file_from_list("latest.json", &articles, 5, lang)?;
+            self.update_root_file_from_list("recommended.json", &articles, usize::MAX, lang)?;
+            self.update_index_json_from_list(&articles, lang)?;
+        }
 
         Ok(())
     }
@@ -236,18 +283,22 @@ impl BlogProcessor {
         filename: &str,
         all_articles: &[Article],
         max_count: usize,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let file_path = Path::new(&self.blog_dir).join(filename);
+        lang: Option<&str>,
+    ) -> Result<()> {
+        let file_path = self
+            .output_root()
+            .join(i18n::localized_file_name(filename, lang));
 
         let mut sorted_articles = all_articles.to_vec();
-        sorted_articles.sort_by(|a, b| b.date.cmp(&a.date));
+        navigation::sort_newest_first(&mut sorted_articles);
 
         if max_count != usize::MAX && sorted_articles.len() > max_count {
             sorted_articles.truncate(max_count);
         }
+        self.filter_extra(filename, &mut sorted_articles);
 
-        let json_content = serde_json::to_string_pretty(&sorted_articles)?;
-        fs::write(&file_path, json_content)?;
+        let json_content = serde_json::to_string_pretty(&sorted_articles).with_path(&file_path)?;
+        self.write_output(&file_path, json_content)?;
 
         Ok(())
     }
@@ -255,11 +306,18 @@ impl BlogProcessor {
     fn update_index_json_from_list(
         &self,
         all_articles: &[Article],
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let file_path = Path::new(&self.blog_dir).join("index.json");
+        lang: Option<&str>,
+    ) -> Result<()> {
+        let file_path = self
+            .output_root()
+            .join(i18n::localized_file_name("index.json", lang));
 
         let mut sorted_articles = all_articles.to_vec();
-        sorted_articles.sort_by(|a, b| b.date.cmp(&a.date));
+        navigation::sort_newest_first(&mut sorted_articles);
+        if self.config.navigation.site_wide {
+            navigation::attach_site_links(&mut sorted_articles);
+        }
+        self.filter_extra("index.json", &mut sorted_articles);
 
         let index_data = IndexJson {
             meta: MetaInfo {
@@ -268,84 +326,754 @@ impl BlogProcessor {
             list: sorted_articles,
         };
 
-        let json_content = serde_json::to_string_pretty(&index_data)?;
-        fs::write(&file_path, json_content)?;
+        let json_content = serde_json::to_string_pretty(&index_data).with_path(&file_path)?;
+        self.write_output(&file_path, json_content)?;
 
         Ok(())
     }
 
-    pub fn process_directory(&self) -> Result<(), Box<dyn std::error::Error>> {
+    /// Processes the whole blog directory and returns how many output files
+    /// were written and how many were already up to date. Outputs are only
+    /// replaced once the whole build has succeeded.
+    pub fn process_directory(&self) -> Result<WriteSummary> {
+        self.commit_build()?;
+        Ok(self.output.summary())
+    }
+
+    /// Processes the whole blog directory like
+    /// [`process_directory`](Self::process_directory), then lints every
+    /// article and checks its links, and reports every source, output, phase
+    /// timing and diagnostic. A failed build is reported in
+    /// [`BuildReport::error`] instead of being returned.
+    pub fn build_report(&self) -> BuildReport {
+        let result = self.commit_build().and_then(|built| {
+            let phase = Instant::now();
+            let (diagnostics, _) = self.source_diagnostics(&built.sources)?;
+            self.recorder.phase("lint", phase.elapsed());
+            Ok(diagnostics)
+        });
+
+        let mut report = self.recorder.take();
+        match result {
+            Ok(diagnostics) => report.diagnostics.extend(diagnostics),
+            Err(error) => report.error = Some(error::chain(&error)),
+        }
+        report.success = report.error.is_none();
+        report
+            .diagnostics
+            .sort_by(|a, b| (&a.file, a.line, a.column).cmp(&(&b.file, b.line, b.column)));
+
+        let root = self.output_root();
+        if report.success {
+            report.summary = self.output.summary();
+            report.outputs = self
+                .output
+                .outcomes()
+                .into_iter()
+                .map(|(path, outcome)| OutputReport {
+                    path: relative_to(&root, &path),
+                    outcome,
+                })
+                .collect();
+        }
+        report
+    }
+
+    /// Runs the build and moves its outputs into place, keeping the previous
+    /// outputs when it fails. Outputs of the previous build that this one no
+    /// longer produces are removed, and the new outputs are recorded in the
+    /// cache for the next build.
+    fn commit_build(&self) -> Result<Built> {
+        self.output.begin();
+
+        match self.build() {
+            Ok(mut built) => {
+                let phase = Instant::now();
+                self.output.commit()?;
+                let outputs = self.output.outputs();
+                for path in self.stale_files(&outputs) {
+                    fs::remove_file(&path).with_path(&path)?;
+                    log::debug!(path = path.to_string_lossy().as_ref(); "Removed {}", path.display());
+                }
+                built.cache.outputs = self.relative_outputs(&outputs);
+                built.cache.output_dir = self.output_dir.clone();
+                built.cache.save(&self.blog_dir)?;
+                self.recorder.phase("commit", phase.elapsed());
+                Ok(built)
+            }
+            Err(error) => {
+                self.output.rollback();
+                Err(error)
+            }
+        }
+    }
+
+    /// Runs the full pipeline in memory and reports every generated file
+    /// that would be created, modified or deleted, without writing anything,
+    /// together with the lint diagnostics and broken links of every article.
+    /// Images and variants not yet in the asset directory are reported as
+    /// created.
+    pub fn check(&self) -> Result<CheckReport> {
+        self.output.begin_dry_run();
+        let result = self.build();
+        let planned = self.output.planned();
+        let outputs = self.output.outputs();
+        self.output.rollback();
+        let built = result?;
+        let (diagnostics, external_links) = self.source_diagnostics(&built.sources)?;
+
+        let mut changes: Vec<FileChange> = planned
+            .into_iter()
+            .map(|write| {
+                let old = fs::read(&write.path).ok();
+                FileChange {
+                    kind: match old {
+                        Some(_) => ChangeKind::Modified,
+                        None => ChangeKind::Created,
+                    },
+                    path: write.path,
+                    old,
+                    new: Some(write.contents),
+                }
+            })
+            .collect();
+
+        for path in self.stale_files(&outputs) {
+            changes.push(FileChange {
+                kind: ChangeKind::Deleted,
+                old: Some(fs::read(&path).with_path(&path)?),
+                new: None,
+                path,
+            });
+        }
+        changes.sort_by(|a, b| a.path.cmp(&b.path));
+
+        Ok(CheckReport {
+            summary: self.output.summary(),
+            changes,
+            diagnostics,
+            external_links,
+        })
+    }
+
+    /// Runs the configured lint rules over every article without writing
+    /// anything.
+    pub fn lint(&self) -> Result<Vec<lint::Diagnostic>> {
+        self.output.begin_dry_run();
+        let result = self.build();
+        self.output.rollback();
+
+        self.lint_sources(&result?.sources)
+    }
+
+    /// Lints `sources` and checks their links, returning the diagnostics
+    /// sorted by file and position together with the external links.
+    fn source_diagnostics(
+        &self,
+        sources: &[(PathBuf, Article)],
+    ) -> Result<(Vec<lint::Diagnostic>, Vec<links::ExternalLink>)> {
+        let link_report = links::check_links(Path::new(&self.blog_dir), sources)?;
+        let mut diagnostics = self.lint_sources(sources)?;
+        diagnostics.extend(link_report.broken);
+        diagnostics.sort_by(|a, b| (&a.file, a.line, a.column).cmp(&(&b.file, b.line, b.column)));
+        Ok((diagnostics, link_report.external))
+    }
+
+    fn lint_sources(&self, sources: &[(PathBuf, Article)]) -> Result<Vec<lint::Diagnostic>> {
+        let blog_path = Path::new(&self.blog_dir);
+        let config_path = self
+            .config
+            .path
+            .clone()
+            .unwrap_or_else(|| blog_path.join(CONFIG_FILE_NAME));
+        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+        let mut diagnostics = Vec::new();
+
+        for (path, article) in sources {
+            let content = fs::read_to_string(path).with_path(path)?;
+            let file = relative_to(blog_path, path);
+            if let Some(doc) = lint::Document::new(&file, article, &content, &today) {
+                diagnostics.extend(self.linter.lint(&doc, &self.config.lint, &config_path)?);
+            }
+        }
+
+        Ok(diagnostics)
+    }
+
+    /// Runs the full pipeline without writing anything and returns every
+    /// article, newest first, as it would be exported.
+    pub fn articles(&self) -> Result<Vec<Article>> {
+        self.output.begin_dry_run();
+        let result = self.build();
+        self.output.rollback();
+
+        let mut articles = result?.articles;
+        navigation::sort_newest_first(&mut articles);
+        Ok(articles)
+    }
+
+    /// Removes every output the last successful build recorded in the cache,
+    /// then the cache itself. Files the build did not write, and outputs of
+    /// builds before the cache recorded them, are never touched. Returns the
+    /// removed paths.
+    pub fn clean(&self) -> Result<Vec<PathBuf>> {
+        let root = self.output_root();
+        let mut removed = Vec::new();
+        for path in self.recorded_outputs() {
+            if path.is_file() {
+                fs::remove_file(&path).with_path(&path)?;
+                removed.push(path);
+            }
+        }
+
+        let cache_path = Path::new(&self.blog_dir).join(cache::CACHE_FILE_NAME);
+        if cache_path.is_file() {
+            fs::remove_file(&cache_path).with_path(&cache_path)?;
+            removed.push(cache_path);
+        }
+
+        // Only drops the directories when nothing else lives in them.
+        let _ = fs::remove_dir(root.join("authors"));
+        let _ = fs::remove_dir(root.join(&self.config.assets.output_dir));
+
+        Ok(removed)
+    }
+
+    /// Recorded outputs of the previous build that are not among `outputs`,
+    /// the outputs of the current build, and are still on disk.
+    fn stale_files(&self, outputs: &BTreeSet<PathBuf>) -> Vec<PathBuf> {
+        let current = self.relative_outputs(outputs);
+        let root = self.output_root();
+        self.recorded_outputs()
+            .into_iter()
+            .filter(|path| !current.contains(&relative_to(&root, path)) && path.is_file())
+            .collect()
+    }
+
+    /// The outputs the last successful build recorded in the cache. None are
+    /// trusted when they were written to a different output directory.
+    fn recorded_outputs(&self) -> Vec<PathBuf> {
+        let cache = BuildCache::load(&self.blog_dir);
+        if cache.output_dir != self.output_dir {
+            return Vec::new();
+        }
+
+        let root = self.output_root();
+        cache.outputs.iter().map(|path| root.join(path)).collect()
+    }
+
+    fn relative_outputs(&self, outputs: &BTreeSet<PathBuf>) -> BTreeSet<String> {
+        let root = self.output_root();
+        outputs
+            .iter()
+            .map(|path| relative_to(&root, path))
+            .collect()
+    }
+
+    /// Runs the full pipeline, staging every output, and returns the cache
+    /// to persist once the outputs are committed together with the articles.
+    fn build(&self) -> Result<Built> {
+        self.recorder.begin();
+        let phase = Instant::now();
         let blog_path = Path::new(&self.blog_dir);
         let mut all_articles = Vec::new();
-        let mut articles_by_category: HashMap<String, Vec<Article>> = HashMap::new();
-
-        for entry in WalkDir::new(blog_path) {
-            let entry = entry?;
-            let path = entry.path();
-
-            if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("md") {
-                println!("Processing file: {:?}", path);
-
-                let content = fs::read_to_string(path)?;
-                if let Some((mut article, _)) = Article::extract_frontmatter(&content) {
-                    if let Some(_relative_path) =
-                        path.strip_prefix(blog_path).ok().and_then(|p| p.to_str())
-                    {
-                        if article.collection.is_none() {
-                            if let Some(parent_dir) = path
-                                .parent()
-                                .and_then(|p| p.file_name())
-                                .and_then(|n| n.to_str())
-                            {
-                                if parent_dir != "images" {
-                                    article.collection = Some(parent_dir.to_string());
-                                }
-                            }
-                        }
-
-                        println!("  - Title: {}", article.title);
-                        println!("  - Date: {}", article.date);
-                        println!("  - Path: {}", article.path);
-
-                        let article_filename = Path::new(&article.path)
-                            .file_name()
-                            .and_then(|name| name.to_str())
-                            .unwrap_or(&article.path);
-
-                        if !all_articles.iter().any(|a: &Article| {
-                            let existing_filename = Path::new(&a.path)
-                                .file_name()
-                                .and_then(|name| name.to_str())
-                                .unwrap_or(&a.path);
-                            existing_filename == article_filename
-                        }) {
-                            all_articles.push(article.clone());
-
-                            if let Some(ref category) = article.collection {
-                                articles_by_category
-                                    .entry(category.clone())
-                                    .or_insert_with(Vec::new)
-                                    .push(article.clone());
-                            }
-                        }
+        let mut built_sources = Vec::new();
+        let mut article_sources = Vec::new();
+        let mut image_manifest: BTreeMap<String, ImageInfo> = BTreeMap::new();
+        let author_profiles = authors::load_authors(&self.blog_dir)?;
+        let previous_cache = if self.force {
+            BuildCache::default()
+        } else {
+            BuildCache::load(&self.blog_dir)
+        };
+        let mut next_cache = BuildCache::default();
+
+        let sources = markdown_sources(blog_path)?;
+        let git_dates = if self.config.dates.from_git {
+            history::load_git_dates(blog_path).unwrap_or_else(|e| {
+                log::warn!("Cannot read dates from git history: {}", error::chain(&e));
+                BTreeMap::new()
+            })
+        } else {
+            BTreeMap::new()
+        };
+
+        self.recorder.phase("load", phase.elapsed());
+        let phase = Instant::now();
+
+        // Parsing is independent per file; results come back in walk order, so
+        // everything below behaves exactly like a single-threaded run.
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(self.jobs)
+            .build()
+            .map_err(io::Error::other)
+            .with_path(blog_path)?;
+        let parsed = pool.install(|| {
+            sources
+                .par_iter()
+                .map(|path| self.parse_source(path, &relative_to(blog_path, path), &previous_cache))
+                .collect::<Result<Vec<_>>>()
+        })?;
+
+        self.recorder.phase("parse", phase.elapsed());
+        let phase = Instant::now();
+
+        for (path, cached) in sources.iter().zip(parsed) {
+            let path = path.as_path();
+            let file = relative_to(blog_path, path);
+            log::trace!(file = file.as_str(); "Processing {}", file);
+
+            next_cache.entries.insert(file.clone(), cached.clone());
+
+            let Some(mut article) = cached.article else {
+                // Markdown without a frontmatter block, such as a README, is
+                // not an article and only worth a debug line.
+                let reason = match cached.error.as_deref() {
+                    Some(error) => {
+                        self.warn(path, "invalid-frontmatter", format!("Skipped: {}", error));
+                        error
                     }
+                    None => {
+                        log::debug!(file = file.as_str(); "Skipped {}: no valid frontmatter", file);
+                        "no valid frontmatter"
+                    }
+                };
+                self.recorder.file(FileReport::skipped(file, reason));
+                continue;
+            };
+
+            if let Some(dates) = git_dates.get(&file) {
+                for warning in history::apply_git_dates(&mut article, dates) {
+                    self.warn(path, "git-dates", warning);
+                }
+            }
+            if article.date.is_empty() || article.update.is_empty() {
+                self.warn(
+                    path,
+                    "missing-date",
+                    "Missing date or update, skipped".to_string(),
+                );
+                self.recorder
+                    .file(FileReport::skipped(file, "missing date or update"));
+                continue;
+            }
+
+            let article = match self.process_article(
+                path,
+                article,
+                &cached.image_sources,
+                &author_profiles,
+                &mut image_manifest,
+            ) {
+                Ok(article) => article,
+                Err(error) => {
+                    self.recorder.file(FileReport::failed(file, &error));
+                    return Err(error);
                 }
+            };
+
+            log::debug!(
+                file = file.as_str(),
+                title = article.title.as_str(),
+                date = article.date.as_str(),
+                path = article.path.as_str();
+                "Built {}: {} ({})", file, article.title, article.date
+            );
+
+            self.recorder.file(FileReport::built(file, &article));
+            built_sources.push((path.to_path_buf(), article.clone()));
+            if !all_articles.iter().any(|a| same_article(a, &article)) {
+                all_articles.push(article);
+                article_sources.push(path.to_path_buf());
             }
         }
 
-        for (category, category_articles) in &articles_by_category {
-            self.update_category_meta_from_list(category, category_articles)?;
+        self.recorder.phase("articles", phase.elapsed());
+        let phase = Instant::now();
+
+        let series_entries = series::attach_series(&mut all_articles, &article_sources)?;
+        navigation::attach_collection_links(&mut all_articles);
+        if self.config.i18n.enabled() {
+            i18n::attach_alternates(&mut all_articles);
         }
 
+        self.update_collection_meta_files(&all_articles, None)?;
         self.update_all_root_json_files(&all_articles)?;
 
+        if self.config.i18n.enabled() {
+            self.update_language_files(&all_articles)?;
+        }
+
+        if !image_manifest.is_empty() {
+            self.update_image_manifest(&image_manifest)?;
+        }
+
+        if !author_profiles.is_empty() {
+            self.update_author_files(&author_profiles, &all_articles)?;
+        }
+
+        if !series_entries.is_empty() {
+            let series_path = self.output_root().join("series.json");
+            let json_content =
+                serde_json::to_string_pretty(&series_entries).with_path(&series_path)?;
+            self.write_output(&series_path, json_content)?;
+        }
+
+        self.recorder.phase("outputs", phase.elapsed());
+
+        Ok(Built {
+            cache: next_cache,
+            articles: all_articles,
+            sources: built_sources,
+        })
+    }
+
+    /// Resolves what an article draws from outside its frontmatter: its
+    /// collection, language, authors, images and cover. Renders its HTML
+    /// when enabled.
+    fn process_article(
+        &self,
+        path: &Path,
+        mut article: Article,
+        image_sources: &[String],
+        author_profiles: &BTreeMap<String, Author>,
+        image_manifest: &mut BTreeMap<String, ImageInfo>,
+    ) -> Result<Article> {
+        let blog_path = Path::new(&self.blog_dir);
+
+        if article.collection.is_none() {
+            article.collection = collection_of(blog_path, path);
+        }
+        if self.config.i18n.enabled() {
+            i18n::assign_language(&mut article, path, &self.config.i18n);
+        }
+
+        article.author_profiles =
+            authors::resolve_authors(&article.authors, author_profiles, path)?;
+
+        article.images = self.process_images(path, image_sources, image_manifest)?;
+        self.resolve_cover(path, &mut article, image_sources, image_manifest)?;
+
+        if self.config.render.html {
+            let content = fs::read_to_string(path).with_path(path)?;
+            let body = Article::split_frontmatter(&content)
+                .map(|(_, body)| body)
+                .unwrap_or("");
+            let html = images::render_html(body, &article.images, "/");
+            let html_path = self
+                .output_root()
+                .join(relative_to(blog_path, path))
+                .with_extension("html");
+            self.write_output(&html_path, html)?;
+        }
+
+        Ok(article)
+    }
+
+    /// Records a warning about a source file in the build report and logs it,
+    /// once per build.
+    fn warn(&self, source: &Path, rule: &str, message: String) {
+        let diagnostic = lint::Diagnostic {
+            rule: rule.to_string(),
+            severity: lint::Severity::Warning,
+            file: relative_to(Path::new(&self.blog_dir), source),
+            line: None,
+            column: None,
+            message,
+        };
+        if self.recorder.diagnostic(diagnostic.clone()) {
+            log::warn!(
+                file = diagnostic.file.as_str(),
+                rule = rule;
+                "{}: {}", diagnostic.file, diagnostic.message
+            );
+        }
+    }
+
+    /// Returns the raw parse result for a Markdown file, reusing the previous
+    /// build's result when the file's stamp or content hash is unchanged.
+    fn parse_source(
+        &self,
+        path: &Path,
+        cache_key: &str,
+        previous_cache: &BuildCache,
+    ) -> Result<CacheEntry> {
+        let (mtime, size) = cache::file_stamp(&fs::metadata(path).with_path(path)?);
+        if let Some(entry) = previous_cache.fresh(cache_key, mtime, size) {
+            return Ok(entry.clone());
+        }
+
+        let content = fs::read_to_string(path).with_path(path)?;
+        let hash = cache::content_hash(content.as_bytes());
+        if let Some(entry) = previous_cache.matching(cache_key, &hash) {
+            return Ok(CacheEntry {
+                mtime,
+                size,
+                ..entry.clone()
+            });
+        }
+
+        let (parsed, error) = match Article::parse_frontmatter(&content) {
+            Some(Ok(parsed)) => (Some(parsed), None),
+            Some(Err(e)) => (None, Some(Article::frontmatter_error(&content, &e))),
+            None => (None, None),
+        };
+        let image_sources = parsed
+            .as_ref()
+            .map(|(_, body)| assets::image_sources(body))
+            .unwrap_or_default();
+
+        Ok(CacheEntry {
+            hash,
+            mtime,
+            size,
+            article: parsed.map(|(article, _)| article),
+            error,
+            image_sources,
+        })
+    }
+
+    /// Writes an output file unless it already holds exactly `contents`,
+    /// so unchanged outputs keep their modification time.
+    fn write_output(&self, path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+        let written = self.output.write(path, contents.as_ref(), self.force)?;
+        if written && !self.output.is_dry_run() {
+            log::debug!(path = path.to_string_lossy().as_ref(); "Wrote {}", path.display());
+        }
         Ok(())
     }
 
-    pub fn update_root_json_files(
+    /// Resolves the image sources of an article, copies each one into the
+    /// asset directory under a content-hashed name and returns their details.
+    /// Missing files are reported and left out.
+    fn process_images(
         &self,
-        article: &Article,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+        source: &Path,
+        sources: &[String],
+        manifest: &mut BTreeMap<String, ImageInfo>,
+    ) -> Result<Vec<ImageInfo>> {
+        let blog_path = Path::new(&self.blog_dir);
+        let asset_dir = self.output_root().join(&self.config.assets.output_dir);
+        let mut images = Vec::new();
+
+        for src in sources {
+            if assets::is_external(src) {
+                continue;
+            }
+
+            let image_path = self.resolve_image_path(source, src);
+
+            if !image_path.is_file() {
+                self.warn(source, "missing-image", format!("Missing image: {}", src));
+                continue;
+            }
+
+            let manifest_key = relative_to(blog_path, &image_path);
+            if let Some(known) = manifest.get(&manifest_key) {
+                images.push(ImageInfo {
+                    src: src.clone(),
+                    ..known.clone()
+                });
+                continue;
+            }
+
+            let content = fs::read(&image_path).with_path(&image_path)?;
+            let file_name = assets::fingerprinted_name(&image_path, &content);
+            let target = asset_dir.join(&file_name);
+            // Fingerprinted names only change with the content, so an
+            // existing copy is never rewritten.
+            if target.exists() {
+                self.output.keep(&target);
+            } else {
+                self.write_output(&target, &content)?;
+            }
+
+            let (width, height) = match imagesize::blob_size(&content) {
+                Ok(size) => (Some(size.width), Some(size.height)),
+                Err(_) => (None, None),
+            };
+
+            let variants = match (width, height) {
+                (Some(width), Some(height)) => images::generate_variants(
+                    &target,
+                    &content,
+                    &self.config.assets.output_dir,
+                    width,
+                    height,
+                    &self.config.images,
+                    &self.output,
+                )
+                .unwrap_or_else(|e| {
+                    self.warn(
+                        source,
+                        "image-variants",
+                        format!("Cannot generate variants for {}: {}", src, error::chain(&e)),
+                    );
+                    Vec::new()
+                }),
+                _ => Vec::new(),
+            };
+
+            let image = ImageInfo {
+                src: src.clone(),
+                path: format!(
+                    "{}/{}",
+                    self.config.assets.output_dir.trim_end_matches('/'),
+                    file_name
+                ),
+                width,
+                height,
+                size: content.len() as u64,
+                variants,
+            };
+
+            manifest.insert(
+                manifest_key.clone(),
+                ImageInfo {
+                    src: manifest_key,
+                    ..image.clone()
+                },
+            );
+            images.push(image);
+        }
+
+        Ok(images)
+    }
+
+    fn resolve_image_path(&self, source: &Path, src: &str) -> PathBuf {
+        let blog_path = Path::new(&self.blog_dir);
+        let local = assets::local_part(src);
+
+        match local.strip_prefix('/') {
+            Some(from_root) => blog_path.join(from_root),
+            None => source.parent().unwrap_or(blog_path).join(local),
+        }
+    }
+
+    /// Picks the article cover, falling back to the first image in the body,
+    /// checks that it lives in the collection's `images/` directory and builds
+    /// the Open Graph block from it.
+    fn resolve_cover(
+        &self,
+        source: &Path,
+        article: &mut Article,
+        sources: &[String],
+        manifest: &mut BTreeMap<String, ImageInfo>,
+    ) -> Result<()> {
+        let blog_path = Path::new(&self.blog_dir);
+        let mut cover_image: Option<ImageInfo> = None;
+
+        if article.cover.is_none() {
+            article.cover = sources.first().cloned();
+        }
+
+        if let Some(cover) = article.cover.clone() {
+            if assets::is_external(&cover) {
+                cover_image = Some(ImageInfo {
+                    src: cover.clone(),
+                    path: cover,
+                    width: None,
+                    height: None,
+                    size: 0,
+                    variants: Vec::new(),
+                });
+            } else {
+                let images_dir = match &article.collection {
+                    Some(collection) => blog_path.join(collection).join("images"),
+                    None => blog_path.join("images"),
+                };
+                let cover_path = relative_to(blog_path, &self.resolve_image_path(source, &cover));
+                let images_dir = relative_to(blog_path, &images_dir);
+
+                if !cover_path.starts_with(&format!("{}/", images_dir)) {
+                    self.warn(
+                        source,
+                        "cover-location",
+                        format!("Cover image outside {}/: {}", images_dir, cover),
+                    );
+                    article.cover = None;
+                } else {
+                    cover_image = match article.images.iter().find(|i| i.src == cover) {
+                        Some(image) => Some(image.clone()),
+                        None => self
+                            .process_images(source, std::slice::from_ref(&cover), manifest)?
+                            .into_iter()
+                            .next(),
+                    };
+                    if cover_image.is_none() {
+                        article.cover = None;
+                    }
+                }
+            }
+        }
+
+        article.og = Some(OpenGraph {
+            kind: "article".to_string(),
+            title: article.title.clone(),
+            description: article
+                .og_description
+                .clone()
+                .unwrap_or_else(|| article.summary.clone()),
+            image: cover_image.as_ref().map(|i| i.path.clone()),
+            image_alt: cover_image.as_ref().and(article.cover_alt.clone()),
+            image_width: cover_image.as_ref().and_then(|i| i.width),
+            image_height: cover_image.as_ref().and_then(|i| i.height),
+            published_time: article.date.clone(),
+            modified_time: article.update.clone(),
+        });
+
+        Ok(())
+    }
+
+    /// Writes `authors.json` with every profile and `authors/<id>.json` with
+    /// each author's articles, newest first.
+    fn update_author_files(
+        &self,
+        author_profiles: &BTreeMap<String, Author>,
+        all_articles: &[Article],
+    ) -> Result<()> {
+        let root = self.output_root();
+        let authors_dir = root.join("authors");
+
+        let profiles: Vec<&Author> = author_profiles.values().collect();
+        let profiles_path = root.join("authors.json");
+        let json_content = serde_json::to_string_pretty(&profiles).with_path(&profiles_path)?;
+        self.write_output(&profiles_path, json_content)?;
+
+        for (id, author) in author_profiles {
+            let mut list: Vec<Article> = all_articles
+                .iter()
+                .filter(|a| a.authors.contains(id))
+                .cloned()
+                .collect();
+            navigation::sort_newest_first(&mut list);
+            self.filter_extra("authors.json", &mut list);
+
+            let author_index = AuthorIndex {
+                author: author.clone(),
+                list,
+            };
+            let index_path = authors_dir.join(format!("{}.json", id));
+            let json_content =
+                serde_json::to_string_pretty(&author_index).with_path(&index_path)?;
+            self.write_output(&index_path, json_content)?;
+        }
+
+        Ok(())
+    }
+
+    fn update_image_manifest(&self, manifest: &BTreeMap<String, ImageInfo>) -> Result<()> {
+        let asset_dir = self.output_root().join(&self.config.assets.output_dir);
+
+        let entries: Vec<&ImageInfo> = manifest.values().collect();
+        let manifest_path = asset_dir.join("manifest.json");
+        let json_content = serde_json::to_string_pretty(&entries).with_path(&manifest_path)?;
+        self.write_output(&manifest_path, json_content)?;
+
+        Ok(())
+    }
+
+    pub fn update_root_json_files(&self, article: &Article) -> Result<()> {
         self.update_root_file("latest.json", article, 5)?;
         self.update_root_file("recommended.json", article, usize::MAX)?;
         self.update_notebooks_json()?;
@@ -355,10 +1083,193 @@ impl BlogProcessor {
     }
 }
 
-fn capitalize_first(s: &str) -> String {
+/// Every Markdown file below the blog root in walk order, leaving out the
+/// archetype templates used by `new`.
+pub(crate) fn markdown_sources(blog_path: &Path) -> Result<Vec<PathBuf>> {
+    let archetypes = blog_path.join(scaffold::ARCHETYPES_DIR);
+    let mut sources = Vec::new();
+
+    for entry in WalkDir::new(blog_path)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| e.path() != archetypes)
+    {
+        let entry = entry?;
+        let path = entry.path();
+
+        if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("md") {
+            sources.push(path.to_path_buf());
+        }
+    }
+
+    Ok(sources)
+}
+
+pub(crate) fn capitalize_first(s: &str) -> String {
     let mut chars = s.chars();
     match chars.next() {
         None => String::new(),
         Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
     }
 }
+
+/// Derives the collection of an article from its directory relative to the
+/// blog root, e.g. `tech/rust/async.md` belongs to `tech/rust`. Files at the
+/// root or inside an `images` directory have no collection.
+fn collection_of(blog_path: &Path, file_path: &Path) -> Option<String> {
+    let parent = file_path.parent()?.strip_prefix(blog_path).ok()?;
+    let segments: Vec<&str> = parent
+        .components()
+        .map(|c| c.as_os_str().to_str())
+        .collect::<Option<_>>()?;
+
+    if segments.is_empty() || segments.contains(&"images") {
+        return None;
+    }
+
+    Some(segments.join("/"))
+}
+
+/// Normalises `path` to a `/`-separated path relative to the blog root,
+/// resolving any `.` and `..` segments along the way.
+pub(crate) fn relative_to(blog_path: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(blog_path).unwrap_or(path);
+    let mut segments: Vec<String> = Vec::new();
+
+    for component in relative.components() {
+        match component {
+            Component::ParentDir => {
+                segments.pop();
+            }
+            Component::Normal(segment) => segments.push(segment.to_string_lossy().to_string()),
+            _ => {}
+        }
+    }
+
+    segments.join("/")
+}
+
+/// Two articles are the same when they share a collection and file name.
+fn same_article(a: &Article, b: &Article) -> bool {
+    fn file_name(article: &Article) -> &str {
+        Path::new(&article.path)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(&article.path)
+    }
+
+    a.collection == b.collection && file_name(a) == file_name(b)
+}
+
+/// Expands the per-collection article lists to every level of the collection
+/// hierarchy, so `tech` gets a listing even if it only holds `tech/rust`.
+fn collection_levels(
+    articles_by_category: &BTreeMap<String, Vec<Article>>,
+    include_descendants: bool,
+) -> BTreeMap<String, Vec<Article>> {
+    let mut levels: BTreeMap<String, Vec<Article>> = BTreeMap::new();
+
+    for (category, articles) in articles_by_category {
+        levels
+            .entry(category.clone())
+            .or_default()
+            .extend(articles.iter().cloned());
+
+        let mut ancestor = category.as_str();
+        while let Some((parent, _)) = ancestor.rsplit_once('/') {
+            let entry = levels.entry(parent.to_string()).or_default();
+            if include_descendants {
+                entry.extend(articles.iter().cloned());
+            }
+            ancestor = parent;
+        }
+    }
+
+    levels
+}
+
+/// Builds the notebook tree for the directory `dir`, whose path relative to
+/// the blog root is `rel`. Only directories containing Markdown files, directly
+/// or in a nested collection, become notebooks.
+fn collect_notebooks(dir: &Path, rel: &str) -> Result<Vec<NotebookEntry>> {
+    let mut notebooks = Vec::new();
+
+    for entry in WalkDir::new(dir)
+        .min_depth(1)
+        .max_depth(1)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        if !entry.file_type().is_dir() {
+            continue;
+        }
+
+        let dir_name = entry.file_name().to_string_lossy().to_string();
+        if dir_name == "images" || (rel.is_empty() && dir_name == scaffold::ARCHETYPES_DIR) {
+            continue;
+        }
+
+        let dir_path = entry.path();
+        let path = if rel.is_empty() {
+            dir_name.clone()
+        } else {
+            format!("{}/{}", rel, dir_name)
+        };
+
+        let children = collect_notebooks(dir_path, &path)?;
+        let has_md_files = dir_path
+            .read_dir()
+            .map(|entries| {
+                entries.filter_map(|e| e.ok()).any(|entry| {
+                    entry
+                        .path()
+                        .extension()
+                        .map(|ext| ext == "md")
+                        .unwrap_or(false)
+                })
+            })
+            .unwrap_or(false);
+
+        if has_md_files || !children.is_empty() {
+            let title = {
+                let name_txt_path = dir_path.join("name.txt");
+                if name_txt_path.exists() {
+                    fs::read_to_string(&name_txt_path)
+                        .with_path(&name_txt_path)?
+                        .trim()
+                        .to_string()
+                } else {
+                    capitalize_first(&dir_name)
+                }
+            };
+
+            notebooks.push(NotebookEntry {
+                id: 0,
+                title,
+                subtitle: dir_name.to_uppercase(),
+                remark: format!("{} articles", capitalize_first(&dir_name)),
+                path,
+                children,
+            });
+        }
+    }
+
+    Ok(notebooks)
+}
+
+/// Gives every notebook of the tree a unique id, level by level, so the
+/// top-level notebooks keep the ids `0..n` they have in a flat blog.
+fn number_notebooks(notebooks: &mut [NotebookEntry]) {
+    let mut next = 0;
+    let mut level: Vec<&mut NotebookEntry> = notebooks.iter_mut().collect();
+    while !level.is_empty() {
+        let mut deeper = Vec::new();
+        for notebook in level {
+            notebook.id = next;
+            next += 1;
+            deeper.extend(notebook.children.iter_mut());
+        }
+        level = deeper;
+    }
+}
@@ -0,0 +1,231 @@
+use crate::Article;
+use crate::error::{self, ZoneError};
+use crate::lint::{Diagnostic, Severity};
+use crate::output::{WriteOutcome, WriteSummary};
+use serde::Serialize;
+use serde_json::json;
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Everything a build did, as returned by
+/// [`BlogProcessor::build_report`](crate::BlogProcessor::build_report).
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct BuildReport {
+    pub success: bool,
+    /// Why the build failed; nothing was written in that case.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub summary: WriteSummary,
+    /// Every Markdown source, in walk order.
+    pub files: Vec<FileReport>,
+    /// Every generated file, sorted by path.
+    pub outputs: Vec<OutputReport>,
+    pub timings: Vec<PhaseTiming>,
+    /// Build warnings, lint findings and broken links.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileReport {
+    /// Path relative to the blog root.
+    pub file: String,
+    pub status: FileStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<FileMetadata>,
+    /// Why the file was skipped or failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl FileReport {
+    pub(crate) fn built(file: String, article: &Article) -> Self {
+        Self {
+            file,
+            status: FileStatus::Built,
+            metadata: Some(article.into()),
+            error: None,
+        }
+    }
+
+    pub(crate) fn skipped(file: String, reason: &str) -> Self {
+        Self {
+            file,
+            status: FileStatus::Skipped,
+            metadata: None,
+            error: Some(reason.to_string()),
+        }
+    }
+
+    pub(crate) fn failed(file: String, error: &ZoneError) -> Self {
+        Self {
+            file,
+            status: FileStatus::Failed,
+            metadata: None,
+            error: Some(error::chain(error)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileStatus {
+    Built,
+    Skipped,
+    Failed,
+}
+
+/// The frontmatter fields identifying a built article.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileMetadata {
+    pub title: String,
+    pub date: String,
+    pub update: String,
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collection: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+}
+
+impl From<&Article> for FileMetadata {
+    fn from(article: &Article) -> Self {
+        Self {
+            title: article.title.clone(),
+            date: article.date.clone(),
+            update: article.update.clone(),
+            path: article.path.clone(),
+            collection: article.collection.clone(),
+            lang: article.lang.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OutputReport {
+    /// Path relative to the output directory.
+    pub path: String,
+    pub outcome: WriteOutcome,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PhaseTiming {
+    pub phase: String,
+    pub duration_ms: f64,
+}
+
+impl BuildReport {
+    /// Renders the diagnostics, and the error of a failed build, as a SARIF
+    /// 2.1.0 log for code scanning tools. File URIs are `base` joined with
+    /// the blog-relative path, so passing the blog directory relative to the
+    /// repository root lets GitHub annotate the right lines. An absolute
+    /// `base` gives `file://` URIs.
+    pub fn to_sarif(&self, base: &Path) -> serde_json::Value {
+        let rules: BTreeSet<&str> = self.diagnostics.iter().map(|d| d.rule.as_str()).collect();
+        let base = base.to_string_lossy().replace('\\', "/");
+        let base = base.trim_start_matches("./").trim_end_matches('/');
+
+        let mut results: Vec<serde_json::Value> = self
+            .diagnostics
+            .iter()
+            .map(|d| {
+                let uri = match base {
+                    "" | "." => d.file.clone(),
+                    base if base.starts_with('/') => format!("file://{}/{}", base, d.file),
+                    base => format!("{}/{}", base, d.file),
+                };
+                let mut location = json!({ "artifactLocation": { "uri": uri } });
+                if let Some(line) = d.line {
+                    location["region"] = json!({
+                        "startLine": line,
+                        "startColumn": d.column.unwrap_or(1),
+                    });
+                }
+
+                json!({
+                    "ruleId": d.rule,
+                    "level": sarif_level(d.severity),
+                    "message": { "text": d.message },
+                    "locations": [{ "physicalLocation": location }],
+                })
+            })
+            .collect();
+
+        if let Some(error) = &self.error {
+            results.push(json!({
+                "ruleId": "build-error",
+                "level": "error",
+                "message": { "text": error },
+            }));
+        }
+
+        json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": env!("CARGO_PKG_NAME"),
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "rules": rules
+                            .iter()
+                            .map(|id| json!({ "id": id }))
+                            .collect::<Vec<_>>(),
+                    }
+                },
+                "results": results,
+            }],
+        })
+    }
+}
+
+fn sarif_level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Off => "none",
+    }
+}
+
+/// Collects the report of the current build while it runs.
+#[derive(Debug, Default)]
+pub(crate) struct Recorder {
+    report: Mutex<BuildReport>,
+}
+
+impl Recorder {
+    pub(crate) fn begin(&self) {
+        *self.lock() = BuildReport::default();
+    }
+
+    pub(crate) fn file(&self, file: FileReport) {
+        self.lock().files.push(file);
+    }
+
+    pub(crate) fn phase(&self, phase: &str, duration: Duration) {
+        self.lock().timings.push(PhaseTiming {
+            phase: phase.to_string(),
+            duration_ms: duration.as_secs_f64() * 1000.0,
+        });
+    }
+
+    /// Records a diagnostic unless the same one was already recorded, and
+    /// returns whether it was new.
+    pub(crate) fn diagnostic(&self, diagnostic: Diagnostic) -> bool {
+        let mut report = self.lock();
+        if report.diagnostics.contains(&diagnostic) {
+            return false;
+        }
+        report.diagnostics.push(diagnostic);
+        true
+    }
+
+    pub(crate) fn take(&self) -> BuildReport {
+        std::mem::take(&mut *self.lock())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BuildReport> {
+        self.report.lock().expect("build report lock poisoned")
+    }
+}
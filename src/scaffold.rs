@@ -0,0 +1,183 @@
+use crate::Article;
+use crate::error::{Result, WithPath, ZoneError};
+use crate::processor::capitalize_first;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+
+/// Directory below the blog root holding archetype templates for `new`. Its
+/// Markdown files are never built as articles.
+pub const ARCHETYPES_DIR: &str = "archetypes";
+
+/// Turns a title into a file name stem: lowercase, with every run of
+/// characters other than letters and digits replaced by a single `-`.
+/// Non-ASCII letters are kept, so `你好 World` becomes `你好-world`.
+pub fn slugify(title: &str) -> String {
+    let mut slug = String::new();
+
+    for c in title.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+
+    slug.trim_end_matches('-').to_string()
+}
+
+/// Creates `<collection>/<slug>.md` below the blog root for `title` dated
+/// `date`, and returns its path. Collection directories are created as
+/// needed, each with a `name.txt`. Existing articles are never overwritten.
+///
+/// The content comes from the closest archetype for the collection (see
+/// [`find_archetype`]) or, without one, a frontmatter block with every
+/// standard field. The result must have valid frontmatter whose `path` is
+/// the new file's name.
+pub fn new_article(blog_dir: &Path, collection: &str, title: &str, date: &str) -> Result<PathBuf> {
+    let collection = normalize_collection(blog_dir, collection)?;
+    let dir = blog_dir.join(&collection);
+    let slug = slugify(title);
+    if slug.is_empty() {
+        return Err(ZoneError::validation(
+            &dir,
+            format!("cannot derive a file name from title '{}'", title),
+        ));
+    }
+
+    let file_name = format!("{}.md", slug);
+    let path = dir.join(&file_name);
+    if path.exists() {
+        return Err(ZoneError::validation(&path, "already exists"));
+    }
+
+    let content = match find_archetype(blog_dir, &collection) {
+        Some(archetype) => {
+            let template = fs::read_to_string(&archetype).with_path(&archetype)?;
+            let content = fill_archetype(&template, title, date, &file_name, &collection);
+            check_frontmatter(&content, &file_name)
+                .map_err(|e| ZoneError::validation(&archetype, e))?;
+            content
+        }
+        None => format!(
+            "+++\ntitle = {}\ndate = \"{}\"\nupdate = \"{}\"\nsummary = \"\"\npath = {}\ncollection = {}\n+++\n\n",
+            toml::Value::String(title.to_string()),
+            date,
+            date,
+            toml::Value::String(file_name.clone()),
+            toml::Value::String(collection.clone()),
+        ),
+    };
+
+    create_collection_dirs(blog_dir, &collection)?;
+    // The check above only saves work; `create_new` is what guarantees a
+    // file created in the meantime is not overwritten.
+    let mut file = match OpenOptions::new().write(true).create_new(true).open(&path) {
+        Ok(file) => file,
+        Err(error) if error.kind() == io::ErrorKind::AlreadyExists => {
+            return Err(ZoneError::validation(&path, "already exists"));
+        }
+        Err(error) => return Err(error).with_path(&path),
+    };
+    file.write_all(content.as_bytes()).with_path(&path)?;
+    Ok(path)
+}
+
+/// Finds the archetype for a collection: `archetypes/tech/rust.md` for
+/// `tech/rust`, then `archetypes/tech.md`, then `archetypes/default.md`.
+pub fn find_archetype(blog_dir: &Path, collection: &str) -> Option<PathBuf> {
+    let archetypes = blog_dir.join(ARCHETYPES_DIR);
+    let mut candidate = collection;
+
+    loop {
+        let path = archetypes.join(format!("{}.md", candidate));
+        if path.is_file() {
+            return Some(path);
+        }
+        match candidate.rsplit_once('/') {
+            Some((parent, _)) => candidate = parent,
+            None => break,
+        }
+    }
+
+    let default = archetypes.join("default.md");
+    default.is_file().then_some(default)
+}
+
+/// Replaces the `{{title}}`, `{{date}}`, `{{path}}`, `{{slug}}` and
+/// `{{collection}}` placeholders. Values are escaped for use inside a TOML
+/// basic string, so templates write `title = "{{title}}"`.
+pub fn fill_archetype(
+    template: &str,
+    title: &str,
+    date: &str,
+    file_name: &str,
+    collection: &str,
+) -> String {
+    let escape = |value: &str| value.replace('\\', "\\\\").replace('"', "\\\"");
+    let slug = file_name.trim_end_matches(".md");
+
+    template
+        .replace("{{title}}", &escape(title))
+        .replace("{{date}}", &escape(date))
+        .replace("{{path}}", &escape(file_name))
+        .replace("{{slug}}", &escape(slug))
+        .replace("{{collection}}", &escape(collection))
+}
+
+fn check_frontmatter(content: &str, file_name: &str) -> Result<(), String> {
+    let (article, _) = Article::extract_frontmatter(content)
+        .ok_or("does not produce valid frontmatter with title, date, update, summary and path")?;
+
+    if article.path != file_name {
+        return Err(format!(
+            "sets path to '{}' instead of '{}'",
+            article.path, file_name
+        ));
+    }
+
+    Ok(())
+}
+
+/// Accepts `tech` or `tech/rust`, rejecting paths that leave the blog root
+/// or point into reserved directories.
+fn normalize_collection(blog_dir: &Path, collection: &str) -> Result<String> {
+    let invalid = || ZoneError::validation(blog_dir.join(collection), "invalid collection");
+    let mut segments = Vec::new();
+
+    for component in Path::new(collection.trim_matches('/')).components() {
+        match component {
+            Component::Normal(segment) => segments.push(segment.to_string_lossy().to_string()),
+            Component::CurDir => {}
+            _ => return Err(invalid()),
+        }
+    }
+
+    if segments.is_empty()
+        || segments.iter().any(|s| s == "images")
+        || segments[0] == ARCHETYPES_DIR
+    {
+        return Err(invalid());
+    }
+
+    Ok(segments.join("/"))
+}
+
+/// Creates every missing level of the collection, and a `name.txt` holding
+/// the default notebook title wherever one is missing along the way.
+fn create_collection_dirs(blog_dir: &Path, collection: &str) -> Result<()> {
+    let mut dir = blog_dir.to_path_buf();
+
+    for segment in collection.split('/') {
+        dir.push(segment);
+        fs::create_dir_all(&dir).with_path(&dir)?;
+
+        let name_txt = dir.join("name.txt");
+        if !name_txt.exists() {
+            fs::write(&name_txt, format!("{}\n", capitalize_first(segment)))
+                .with_path(&name_txt)?;
+        }
+    }
+
+    Ok(())
+}
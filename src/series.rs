@@ -0,0 +1,121 @@
+use crate::Article;
+use crate::error::{Result, ZoneError};
+use crate::models::{ArticleLink, SeriesEntry, SeriesInfo, SeriesPart};
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Frontmatter names a series with a plain string, while generated listings
+/// carry the full `SeriesInfo`; accept either form.
+pub fn deserialize_series<'de, D>(deserializer: D) -> Result<Option<SeriesInfo>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum NameOrInfo {
+        Name(String),
+        Info(SeriesInfo),
+    }
+
+    Ok(
+        Option::<NameOrInfo>::deserialize(deserializer)?.map(|value| match value {
+            NameOrInfo::Name(name) => SeriesInfo {
+                name,
+                index: 0,
+                total: 0,
+                prev: None,
+                next: None,
+            },
+            NameOrInfo::Info(info) => info,
+        }),
+    )
+}
+
+/// Groups articles by series and language across collections, fills in each member's
+/// position and prev/next links, and returns the entries for `series.json`.
+/// Every member needs a `series_order`, and the orders of a series must run
+/// from 1 without duplicates or gaps. `sources` holds the source file of each
+/// article, which errors name.
+pub fn attach_series(articles: &mut [Article], sources: &[PathBuf]) -> Result<Vec<SeriesEntry>> {
+    // (series name, language) -> (series_order, article index)
+    type SeriesKey = (String, Option<String>);
+    let mut members: BTreeMap<SeriesKey, Vec<(u32, usize)>> = BTreeMap::new();
+
+    for (idx, article) in articles.iter().enumerate() {
+        if let Some(series) = &article.series {
+            let order = article.series_order.ok_or_else(|| {
+                ZoneError::validation(
+                    sources[idx].clone(),
+                    format!("article in series '{}' has no series_order", series.name),
+                )
+            })?;
+            members
+                .entry((series.name.clone(), article.lang.clone()))
+                .or_default()
+                .push((order, idx));
+        }
+    }
+
+    let mut entries = Vec::new();
+
+    for ((name, lang), mut parts) in members {
+        parts.sort_by_key(|(order, _)| *order);
+
+        if let Some(pair) = parts.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+            return Err(ZoneError::validation(
+                sources[pair[1].1].clone(),
+                format!(
+                    "series '{}': duplicate series_order {} (also used by {})",
+                    name,
+                    pair[0].0,
+                    sources[pair[0].1].display()
+                ),
+            ));
+        }
+
+        for (expected, (order, idx)) in (1u32..).zip(&parts) {
+            if *order != expected {
+                return Err(ZoneError::validation(
+                    sources[*idx].clone(),
+                    format!(
+                        "series '{}': missing series_order {} (next part has order {})",
+                        name, expected, order
+                    ),
+                ));
+            }
+        }
+
+        let links: Vec<ArticleLink> = parts
+            .iter()
+            .map(|(_, idx)| ArticleLink::from(&articles[*idx]))
+            .collect();
+        let total = parts.len();
+
+        for (position, (_, idx)) in parts.iter().enumerate() {
+            articles[*idx].series = Some(SeriesInfo {
+                name: name.clone(),
+                index: position + 1,
+                total,
+                prev: position.checked_sub(1).map(|p| links[p].clone()),
+                next: links.get(position + 1).cloned(),
+            });
+        }
+
+        entries.push(SeriesEntry {
+            name,
+            lang,
+            total,
+            parts: parts
+                .iter()
+                .zip(links)
+                .map(|((order, _), link)| SeriesPart {
+                    order: *order,
+                    link,
+                })
+                .collect(),
+        });
+    }
+
+    Ok(entries)
+}
@@ -0,0 +1,273 @@
+use crate::error::{Result, WithPath, ZoneError};
+use crate::{BlogProcessor, watch};
+use std::fs::File;
+use std::io::{self, Write};
+use std::net::SocketAddr;
+use std::path::{Component, Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// Path of the Server-Sent Events stream announcing completed rebuilds.
+pub const EVENTS_PATH: &str = "/__zone/events";
+
+/// Comment lines sent to idle event streams so closed connections are noticed.
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+/// Serves the files of a blog directory over HTTP and pushes a `reload`
+/// event to every connected client after each rebuild.
+pub struct PreviewServer {
+    server: Arc<Server>,
+    root: PathBuf,
+    cors_origin: String,
+    clients: Arc<Mutex<Vec<Sender<u64>>>>,
+    builds: u64,
+}
+
+impl PreviewServer {
+    /// Binds to `addr`; use port 0 to let the OS pick a free port. Failing to
+    /// bind, or a `cors_origin` that cannot be sent as a header value, is
+    /// reported against `root`.
+    pub fn bind(addr: &str, root: impl Into<PathBuf>, cors_origin: &str) -> Result<Self> {
+        let root = root.into();
+        if !is_header_value(cors_origin) {
+            return Err(ZoneError::config(
+                &root,
+                format!("invalid CORS origin {:?}", cors_origin),
+            ));
+        }
+        let server = Server::http(addr)
+            .map_err(io::Error::other)
+            .with_path(&root)?;
+        Ok(Self {
+            server: Arc::new(server),
+            root,
+            cors_origin: cors_origin.to_string(),
+            clients: Arc::new(Mutex::new(Vec::new())),
+            builds: 0,
+        })
+    }
+
+    pub fn addr(&self) -> Option<SocketAddr> {
+        self.server.server_addr().to_ip()
+    }
+
+    /// Starts answering requests on background threads, one per request so
+    /// that long-lived event streams do not block file requests.
+    pub fn spawn(&self) {
+        let server = Arc::clone(&self.server);
+        let root = self.root.clone();
+        let cors_origin = self.cors_origin.clone();
+        let clients = Arc::clone(&self.clients);
+
+        thread::spawn(move || {
+            for request in server.incoming_requests() {
+                let root = root.clone();
+                let cors_origin = cors_origin.clone();
+                let clients = Arc::clone(&clients);
+                thread::spawn(move || {
+                    if let Err(e) = handle(request, &root, &cors_origin, &clients) {
+                        log::warn!("Request failed: {}", e);
+                    }
+                });
+            }
+        });
+    }
+
+    /// Sends a `reload` event to every connected client, dropping clients
+    /// that have disconnected.
+    pub fn notify_reload(&mut self) {
+        self.builds += 1;
+        let builds = self.builds;
+        let mut clients = self.clients.lock().expect("client list poisoned");
+        clients.retain(|client| client.send(builds).is_ok());
+    }
+}
+
+/// Builds the site, serves it on `addr` and rebuilds on changes, notifying
+/// connected clients after each rebuild that changed an output. Runs until
+/// the watcher stops.
+pub fn serve(
+    processor: &mut BlogProcessor,
+    config_path: &Path,
+    addr: &str,
+    cors_origin: &str,
+    debounce: Duration,
+) -> Result<()> {
+    let mut server = PreviewServer::bind(addr, processor.output_root(), cors_origin)?;
+    server.spawn();
+
+    match server.addr() {
+        Some(addr) => log::info!("Serving {} at http://{}/", server.root.display(), addr),
+        None => log::info!("Serving {}", server.root.display()),
+    }
+    log::info!("Live reload events: {}", EVENTS_PATH);
+
+    watch::watch(processor, config_path, debounce, |summary| {
+        if summary.written > 0 {
+            server.notify_reload();
+        }
+    })
+}
+
+fn handle(
+    request: Request,
+    root: &Path,
+    cors_origin: &str,
+    clients: &Mutex<Vec<Sender<u64>>>,
+) -> io::Result<()> {
+    let path = request
+        .url()
+        .split(['?', '#'])
+        .next()
+        .unwrap_or("/")
+        .to_string();
+
+    match request.method() {
+        Method::Options => {
+            return request.respond(with_cors(Response::empty(204), cors_origin));
+        }
+        Method::Get | Method::Head => {}
+        _ => {
+            let response = Response::from_string("Method Not Allowed").with_status_code(405);
+            return request.respond(with_cors(response, cors_origin));
+        }
+    }
+
+    if path == EVENTS_PATH {
+        return stream_events(request, cors_origin, clients);
+    }
+
+    let Some(file_path) = resolve(root, &path) else {
+        let response = Response::from_string("Not Found").with_status_code(404);
+        return request.respond(with_cors(response, cors_origin));
+    };
+
+    let response = Response::from_file(File::open(&file_path)?)
+        .with_header(header("Content-Type", content_type(&file_path)))
+        .with_header(header("Cache-Control", "no-cache"));
+    request.respond(with_cors(response, cors_origin))
+}
+
+/// Holds the connection open and writes one `reload` event per rebuild.
+fn stream_events(
+    request: Request,
+    cors_origin: &str,
+    clients: &Mutex<Vec<Sender<u64>>>,
+) -> io::Result<()> {
+    let (tx, rx) = mpsc::channel();
+    clients.lock().expect("client list poisoned").push(tx);
+
+    let mut writer = request.into_writer();
+    write!(
+        writer,
+        "HTTP/1.1 200 OK\r\n\
+         Content-Type: text/event-stream\r\n\
+         Cache-Control: no-cache\r\n\
+         Connection: keep-alive\r\n\
+         Access-Control-Allow-Origin: {}\r\n\r\n\
+         : connected\n\n",
+        cors_origin
+    )?;
+    writer.flush()?;
+
+    loop {
+        match rx.recv_timeout(KEEP_ALIVE) {
+            Ok(build) => write!(writer, "event: reload\ndata: {}\n\n", build)?,
+            Err(RecvTimeoutError::Timeout) => write!(writer, ": keep-alive\n\n")?,
+            Err(RecvTimeoutError::Disconnected) => return Ok(()),
+        }
+        writer.flush()?;
+    }
+}
+
+/// Maps a request path to a file below `root`. Parent components and hidden
+/// files such as `.zone-cache` are never served; directories fall back to
+/// their `index.html`.
+pub fn resolve(root: &Path, url_path: &str) -> Option<PathBuf> {
+    let decoded = percent_decode(url_path)?;
+    let mut path = root.to_path_buf();
+
+    for component in Path::new(decoded.trim_start_matches('/')).components() {
+        match component {
+            Component::Normal(part) if !part.to_string_lossy().starts_with('.') => path.push(part),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+
+    if path.is_dir() {
+        path.push("index.html");
+    }
+    path.is_file().then_some(path)
+}
+
+fn percent_decode(value: &str) -> Option<String> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = value.get(i + 1..i + 3)?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    String::from_utf8(decoded).ok()
+}
+
+pub fn content_type(path: &Path) -> &'static str {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+
+    match ext.as_str() {
+        "json" => "application/json; charset=utf-8",
+        "html" | "htm" => "text/html; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "js" | "mjs" => "text/javascript; charset=utf-8",
+        "xml" => "application/xml; charset=utf-8",
+        "md" => "text/markdown; charset=utf-8",
+        "txt" => "text/plain; charset=utf-8",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "avif" => "image/avif",
+        "ico" => "image/x-icon",
+        "woff2" => "font/woff2",
+        _ => "application/octet-stream",
+    }
+}
+
+fn with_cors<R: io::Read>(response: Response<R>, cors_origin: &str) -> Response<R> {
+    response
+        .with_header(header("Access-Control-Allow-Origin", cors_origin))
+        .with_header(header("Access-Control-Allow-Methods", "GET, HEAD, OPTIONS"))
+        .with_header(header("Access-Control-Allow-Headers", "*"))
+}
+
+/// Only called with fixed names and values and with the CORS origin, which
+/// [`PreviewServer::bind`] has checked, so it never fails.
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name, value).expect("valid header")
+}
+
+/// A non-empty run of visible ASCII characters, spaces and tabs, which keeps
+/// the value from ending the header line early.
+fn is_header_value(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .bytes()
+            .all(|b| b == b' ' || b == b'\t' || b.is_ascii_graphic())
+}
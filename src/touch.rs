@@ -0,0 +1,125 @@
+use crate::Article;
+use crate::cache::{self, BuildCache, CACHE_FILE_NAME};
+use crate::error::{Result, WithPath, ZoneError};
+use crate::processor::{markdown_sources, relative_to};
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Sets the `update` field in an article's frontmatter to `date` and changes
+/// nothing else: the value keeps its quoting style and a trailing comment
+/// stays in place. A missing field is added after `date`. Returns `None` when
+/// the content has no frontmatter.
+pub fn set_update_date(content: &str, date: &str) -> Option<String> {
+    let (frontmatter, _) = Article::split_frontmatter(content)?;
+    let start = frontmatter.as_ptr() as usize - content.as_ptr() as usize;
+
+    let mut date_line_end = None;
+    let mut offset = start;
+    for line in frontmatter.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+
+        // Keys below a table header belong to that table.
+        if line.trim_start().starts_with('[') {
+            break;
+        }
+
+        if let Some(value) = value_range(line, "update") {
+            let range = line_start + value.start..line_start + value.end;
+            let replacement = match content[range.clone()].chars().next() {
+                Some(quote @ ('"' | '\'')) => format!("{}{}{}", quote, date, quote),
+                _ => date.to_string(),
+            };
+
+            let mut updated = content.to_string();
+            updated.replace_range(range, &replacement);
+            return Some(updated);
+        }
+
+        if value_range(line, "date").is_some() {
+            date_line_end = Some(offset);
+        }
+    }
+
+    let mut updated = content.to_string();
+    match date_line_end {
+        Some(end) if content[..end].ends_with('\n') => {
+            updated.insert_str(end, &format!("update = \"{}\"\n", date));
+        }
+        _ => {
+            let end = start + frontmatter.len();
+            updated.insert_str(end, &format!("\nupdate = \"{}\"", date));
+        }
+    }
+    Some(updated)
+}
+
+/// The byte range of the value in a `key = value` line, without any trailing
+/// whitespace or comment.
+fn value_range(line: &str, key: &str) -> Option<Range<usize>> {
+    let indent = line.len() - line.trim_start().len();
+    let rest = line[indent..].strip_prefix(key)?;
+    let after_key = rest.trim_start();
+    if !after_key.starts_with('=') {
+        return None;
+    }
+
+    let value_start = line.len() - after_key[1..].trim_start().len();
+    let value = &line[value_start..];
+    let len = match value.chars().next()? {
+        quote @ ('"' | '\'') => value[1..].find(quote)? + 2,
+        _ => value
+            .find(|c: char| c.is_whitespace() || c == '#')
+            .unwrap_or(value.len()),
+    };
+
+    (len > 0).then_some(value_start..value_start + len)
+}
+
+/// The Markdown files whose content changed since the last build, according
+/// to the content hashes in `.zone-cache`. Files the last build did not see
+/// are left out. Fails when there is no cache to compare against.
+pub fn changed_since_build(blog_dir: &str) -> Result<Vec<PathBuf>> {
+    let blog_path = Path::new(blog_dir);
+    let cache = BuildCache::load(blog_dir);
+    if cache.entries.is_empty() {
+        return Err(ZoneError::validation(
+            blog_path.join(CACHE_FILE_NAME),
+            "no build cache found; build first or name the articles to touch",
+        ));
+    }
+
+    let mut changed = Vec::new();
+    for path in markdown_sources(blog_path)? {
+        if let Some(entry) = cache.entries.get(&relative_to(blog_path, &path))
+            && entry.hash != cache::content_hash(&fs::read(&path).with_path(&path)?)
+        {
+            changed.push(path);
+        }
+    }
+
+    Ok(changed)
+}
+
+/// Sets the `update` date of every file in `paths`, returning the files that
+/// changed. Fails on files without frontmatter before writing anything.
+pub fn touch(paths: &[PathBuf], date: &str) -> Result<Vec<PathBuf>> {
+    let mut updates = Vec::new();
+    for path in paths {
+        let content = fs::read_to_string(path).with_path(path)?;
+        let updated = set_update_date(&content, date)
+            .ok_or_else(|| ZoneError::validation(path, "has no frontmatter"))?;
+        if updated != content {
+            updates.push((path.clone(), updated));
+        }
+    }
+
+    let mut touched = Vec::new();
+    for (path, updated) in updates {
+        fs::write(&path, updated).with_path(&path)?;
+        touched.push(path);
+    }
+
+    Ok(touched)
+}
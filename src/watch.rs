@@ -0,0 +1,163 @@
+use crate::authors::AUTHORS_FILE_NAME;
+use crate::cache;
+use crate::config::CONFIG_FILE_NAME;
+use crate::error::{self, Result, WithPath};
+use crate::{BlogProcessor, Config, WriteSummary};
+use notify_debouncer_mini::new_debouncer;
+use notify_debouncer_mini::notify::RecursiveMode;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+use walkdir::WalkDir;
+
+/// Whether a change to `path` should trigger a rebuild. Only sources count:
+/// Markdown files, `name.txt` and the configuration files. Generated JSON,
+/// HTML, assets and temporary files are ignored so a rebuild never triggers
+/// itself.
+pub fn is_watched(path: &Path) -> bool {
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+
+    file_name == "name.txt"
+        || file_name == CONFIG_FILE_NAME
+        || file_name == AUTHORS_FILE_NAME
+        || path.extension().is_some_and(|ext| ext == "md")
+}
+
+/// Watches the blog directory and rebuilds after every burst of source
+/// changes. Events arriving within `debounce` of each other are handled as a
+/// single rebuild. The configuration is reloaded from `config_path` when a
+/// file with its name changes; its directory is watched too when it lies
+/// outside the blog directory. Thanks to the build cache only changed files are parsed
+/// again, and only outputs whose content changes are rewritten.
+/// `on_rebuild` runs after every successful rebuild. Runs until the watcher
+/// stops.
+pub fn watch(
+    processor: &mut BlogProcessor,
+    config_path: &Path,
+    debounce: Duration,
+    mut on_rebuild: impl FnMut(&WriteSummary),
+) -> Result<()> {
+    let blog_dir = PathBuf::from(&processor.blog_dir);
+    let (tx, rx) = mpsc::channel();
+    let mut debouncer = new_debouncer(debounce, tx)
+        .map_err(io::Error::other)
+        .with_path(&blog_dir)?;
+    debouncer
+        .watcher()
+        .watch(&blog_dir, RecursiveMode::Recursive)
+        .map_err(io::Error::other)
+        .with_path(&blog_dir)?;
+
+    let config_dir = match config_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    if !is_within(config_dir, &blog_dir) {
+        debouncer
+            .watcher()
+            .watch(config_dir, RecursiveMode::NonRecursive)
+            .map_err(io::Error::other)
+            .with_path(config_dir)?;
+    }
+
+    log::info!("Watching {} for changes...", processor.blog_dir);
+
+    let mut seen = snapshot(&blog_dir, config_path);
+
+    for result in rx {
+        let changed: BTreeSet<PathBuf> = result
+            .map_err(io::Error::other)
+            .with_path(&blog_dir)?
+            .into_iter()
+            .map(|event| event.path)
+            .filter(|path| is_watched(path) || path.file_name() == config_path.file_name())
+            .filter(|path| is_modified(&mut seen, path))
+            .collect();
+
+        if changed.is_empty() {
+            continue;
+        }
+
+        for path in &changed {
+            log::info!(path = path.to_string_lossy().as_ref(); "Changed: {}", path.display());
+        }
+
+        if changed
+            .iter()
+            .any(|path| path.file_name() == config_path.file_name())
+        {
+            match Config::load_from(config_path) {
+                Ok(config) => processor.config = config,
+                Err(e) => log::warn!("Keeping previous configuration: {}", error::chain(&e)),
+            }
+        }
+
+        match processor.process_directory() {
+            Ok(summary) => {
+                log::info!(
+                    written = summary.written,
+                    unchanged = summary.unchanged;
+                    "Rebuilt: {} files written, {} unchanged.",
+                    summary.written,
+                    summary.unchanged
+                );
+                on_rebuild(&summary);
+            }
+            Err(e) => log::error!("Build failed: {}", error::chain(&e)),
+        }
+    }
+
+    Ok(())
+}
+
+/// Modification time and size of a watched file, `None` once it is gone.
+type Stamp = Option<(u64, u64)>;
+
+/// Records the stamp of every watched source and of the configuration file
+/// when watching starts, under the canonical paths events report.
+fn snapshot(blog_dir: &Path, config_path: &Path) -> BTreeMap<PathBuf, Stamp> {
+    let root = blog_dir
+        .canonicalize()
+        .unwrap_or_else(|_| blog_dir.to_path_buf());
+    let mut paths: Vec<PathBuf> = WalkDir::new(root)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file() && is_watched(entry.path()))
+        .map(|entry| entry.into_path())
+        .collect();
+    paths.extend(config_path.canonicalize().ok());
+
+    paths
+        .into_iter()
+        .map(|path| {
+            let stamp = stamp(&path);
+            (path, stamp)
+        })
+        .collect()
+}
+
+fn stamp(path: &Path) -> Stamp {
+    fs::metadata(path).ok().map(|m| cache::file_stamp(&m))
+}
+
+/// Whether `path` was modified, created or removed since it was last seen.
+/// Reading a file during a rebuild raises an event too, so the stamp decides;
+/// a path missing from the record is new and always counts, whatever its
+/// modification time.
+fn is_modified(seen: &mut BTreeMap<PathBuf, Stamp>, path: &Path) -> bool {
+    let stamp = stamp(path);
+    seen.insert(path.to_path_buf(), stamp)
+        .is_none_or(|previous| previous != stamp)
+}
+
+/// Whether `dir` is `root` or lies below it, comparing canonical paths when
+/// both exist.
+fn is_within(dir: &Path, root: &Path) -> bool {
+    match (dir.canonicalize(), root.canonicalize()) {
+        (Ok(dir), Ok(root)) => dir.starts_with(root),
+        _ => dir.starts_with(root),
+    }
+}
@@ -1,3 +1,6 @@
+// Written before let-chains were stable.
+#![allow(clippy::collapsible_if, clippy::expect_fun_call)]
+
 use serde::{Deserialize, Serialize};
 use std::fs;
 use tempfile::TempDir;
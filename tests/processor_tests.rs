@@ -0,0 +1,1564 @@
+use std::fs;
+use tempfile::TempDir;
+use zone_builder::{
+    Article, Author, AuthorIndex, BlogProcessor, ChangeKind, Config, ImageInfo, NotebookEntry,
+    SeriesEntry, Severity, ZoneError, assets,
+};
+
+fn write_article(dir: &std::path::Path, file_name: &str, title: &str, date: &str) {
+    fs::create_dir_all(dir).expect("Failed to create article dir");
+    let content = format!(
+        "+++\ntitle = \"{title}\"\ndate = \"{date}\"\nupdate = \"{date}\"\nsummary = \"Summary of {title}\"\npath = \"{file_name}\"\n+++\n\nBody of {title}.\n"
+    );
+    fs::write(dir.join(file_name), content).expect("Failed to write article");
+}
+
+fn read_articles(path: &std::path::Path) -> Vec<Article> {
+    let content = fs::read_to_string(path).expect("Failed to read listing");
+    serde_json::from_str(&content).expect("Failed to parse listing")
+}
+
+#[test]
+fn test_nested_collections() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+
+    write_article(&root.join("tech"), "intro.md", "Intro", "2024-01-01");
+    write_article(&root.join("tech/rust"), "async.md", "Async", "2024-02-01");
+
+    let processor = BlogProcessor::new(root.to_str().unwrap().to_string());
+    processor
+        .process_directory()
+        .expect("Failed to process directory");
+
+    let nested = read_articles(&root.join("tech/rust/meta.json"));
+    assert_eq!(nested.len(), 1);
+    assert_eq!(nested[0].collection.as_deref(), Some("tech/rust"));
+
+    let parent = read_articles(&root.join("tech/meta.json"));
+    assert_eq!(parent.len(), 1);
+    assert_eq!(parent[0].title, "Intro");
+
+    let notebooks: Vec<NotebookEntry> = serde_json::from_str(
+        &fs::read_to_string(root.join("notebooks.json")).expect("Failed to read notebooks"),
+    )
+    .expect("Failed to parse notebooks");
+    assert_eq!(notebooks.len(), 1);
+    assert_eq!(notebooks[0].path, "tech");
+    assert_eq!(notebooks[0].children.len(), 1);
+    assert_eq!(notebooks[0].children[0].path, "tech/rust");
+    assert_ne!(notebooks[0].children[0].id, notebooks[0].id);
+}
+
+#[test]
+fn test_nested_collections_include_descendants() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+
+    write_article(&root.join("tech/rust"), "async.md", "Async", "2024-02-01");
+    fs::write(
+        root.join("zone.toml"),
+        "[collections]\ninclude_descendants = true\n",
+    )
+    .expect("Failed to write config");
+
+    let blog_dir = root.to_str().unwrap().to_string();
+    let config = Config::load(&blog_dir).expect("Failed to load config");
+    let processor = BlogProcessor::with_config(blog_dir, config);
+    processor
+        .process_directory()
+        .expect("Failed to process directory");
+
+    let parent = read_articles(&root.join("tech/meta.json"));
+    assert_eq!(parent.len(), 1);
+    assert_eq!(parent[0].title, "Async");
+}
+
+fn png_header(width: u32, height: u32) -> Vec<u8> {
+    let mut bytes = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR".to_vec();
+    bytes.extend_from_slice(&width.to_be_bytes());
+    bytes.extend_from_slice(&height.to_be_bytes());
+    bytes.extend_from_slice(&[8, 6, 0, 0, 0, 0, 0, 0, 0]);
+    bytes
+}
+
+#[test]
+fn test_article_images_are_fingerprinted() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+    let tech_dir = root.join("tech");
+
+    fs::create_dir_all(tech_dir.join("images")).expect("Failed to create images dir");
+    fs::write(tech_dir.join("images/chart.png"), png_header(640, 480))
+        .expect("Failed to write image");
+    fs::write(
+        tech_dir.join("charts.md"),
+        "+++\ntitle = \"Charts\"\ndate = \"2024-01-01\"\nupdate = \"2024-01-01\"\nsummary = \"\"\npath = \"charts.md\"\n+++\n\n![Chart](images/chart.png)\n![Gone](images/missing.png)\n",
+    )
+    .expect("Failed to write article");
+
+    let processor = BlogProcessor::new(root.to_str().unwrap().to_string());
+    processor
+        .process_directory()
+        .expect("Failed to process directory");
+
+    let articles = read_articles(&tech_dir.join("meta.json"));
+    assert_eq!(articles[0].images.len(), 1);
+
+    let image = &articles[0].images[0];
+    assert_eq!(image.src, "images/chart.png");
+    assert_eq!(image.width, Some(640));
+    assert_eq!(image.height, Some(480));
+    assert!(image.path.starts_with("assets/chart."));
+    assert!(root.join(&image.path).exists());
+
+    let manifest: Vec<ImageInfo> = serde_json::from_str(
+        &fs::read_to_string(root.join("assets/manifest.json")).expect("Failed to read manifest"),
+    )
+    .expect("Failed to parse manifest");
+    assert_eq!(manifest.len(), 1);
+    assert_eq!(manifest[0].src, "tech/images/chart.png");
+}
+
+#[test]
+fn test_rewrite_image_links() {
+    let images = vec![ImageInfo {
+        src: "images/a.png".to_string(),
+        path: "assets/a.0011223344556677.png".to_string(),
+        width: None,
+        height: None,
+        size: 0,
+        variants: Vec::new(),
+    }];
+    let body = "![A](images/a.png \"Title\") and ![B][b]\n\n[b]: images/a.png\n";
+
+    let rewritten = assets::rewrite_image_links(body, &images, "/");
+
+    assert_eq!(
+        rewritten,
+        "![A](/assets/a.0011223344556677.png \"Title\") and ![B][b]\n\n[b]: /assets/a.0011223344556677.png\n"
+    );
+}
+
+#[test]
+fn test_responsive_image_variants() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+    let tech_dir = root.join("tech");
+
+    fs::create_dir_all(tech_dir.join("images")).expect("Failed to create images dir");
+    image::RgbImage::new(1000, 500)
+        .save(tech_dir.join("images/wide.png"))
+        .expect("Failed to write image");
+    fs::write(
+        tech_dir.join("wide.md"),
+        "+++\ntitle = \"Wide\"\ndate = \"2024-01-01\"\nupdate = \"2024-01-01\"\nsummary = \"\"\npath = \"wide.md\"\n+++\n\n![Wide shot](images/wide.png)\n",
+    )
+    .expect("Failed to write article");
+    fs::write(
+        root.join("zone.toml"),
+        "[images]\nwidths = [480, 2000]\nformats = [\"webp\"]\n\n[render]\nhtml = true\n",
+    )
+    .expect("Failed to write config");
+
+    let blog_dir = root.to_str().unwrap().to_string();
+    let config = Config::load(&blog_dir).expect("Failed to load config");
+    BlogProcessor::with_config(blog_dir, config)
+        .process_directory()
+        .expect("Failed to process directory");
+
+    let articles = read_articles(&tech_dir.join("meta.json"));
+    let variants = &articles[0].images[0].variants;
+    let summary: Vec<(&str, usize, usize)> = variants
+        .iter()
+        .map(|v| (v.format.as_str(), v.width, v.height))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("image/png", 480, 240),
+            ("image/webp", 480, 240),
+            ("image/webp", 1000, 500),
+        ]
+    );
+    for variant in variants {
+        assert!(root.join(&variant.path).exists());
+    }
+
+    let html = fs::read_to_string(tech_dir.join("wide.html")).expect("Failed to read html");
+    assert!(html.contains("<picture><source type=\"image/webp\""));
+    assert!(html.contains("alt=\"Wide shot\""));
+    assert!(html.contains("width=\"1000\" height=\"500\""));
+}
+
+#[test]
+fn test_cover_and_open_graph() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+    let tech_dir = root.join("tech");
+
+    fs::create_dir_all(tech_dir.join("images")).expect("Failed to create images dir");
+    fs::write(tech_dir.join("images/cover.png"), png_header(1200, 630))
+        .expect("Failed to write image");
+    fs::write(tech_dir.join("stray.png"), png_header(10, 10)).expect("Failed to write image");
+    fs::write(
+        tech_dir.join("with-cover.md"),
+        "+++\ntitle = \"With Cover\"\ndate = \"2024-01-01\"\nupdate = \"2024-01-02\"\nsummary = \"Summary\"\npath = \"with-cover.md\"\ncover = \"images/cover.png\"\ncover_alt = \"A cover\"\nog_description = \"Shared text\"\n+++\n\nNo inline images.\n",
+    )
+    .expect("Failed to write article");
+    fs::write(
+        tech_dir.join("stray-cover.md"),
+        "+++\ntitle = \"Stray Cover\"\ndate = \"2024-01-03\"\nupdate = \"2024-01-03\"\nsummary = \"Fallback\"\npath = \"stray-cover.md\"\n+++\n\n![Stray](stray.png)\n",
+    )
+    .expect("Failed to write article");
+
+    BlogProcessor::new(root.to_str().unwrap().to_string())
+        .process_directory()
+        .expect("Failed to process directory");
+
+    let articles = read_articles(&tech_dir.join("meta.json"));
+
+    let stray = &articles[0];
+    assert_eq!(stray.title, "Stray Cover");
+    assert_eq!(stray.cover, None);
+    let og = stray.og.as_ref().expect("og block");
+    assert_eq!(og.description, "Fallback");
+    assert_eq!(og.image, None);
+
+    let covered = &articles[1];
+    assert_eq!(covered.cover.as_deref(), Some("images/cover.png"));
+    let og = covered.og.as_ref().expect("og block");
+    assert_eq!(og.kind, "article");
+    assert_eq!(og.description, "Shared text");
+    assert_eq!(og.image_alt.as_deref(), Some("A cover"));
+    assert_eq!(og.image_width, Some(1200));
+    assert!(og.image.as_deref().unwrap().starts_with("assets/cover."));
+}
+
+#[test]
+fn test_extra_frontmatter_passthrough() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+    let tech_dir = root.join("tech");
+
+    fs::create_dir_all(&tech_dir).expect("Failed to create dir");
+    fs::write(
+        tech_dir.join("extra.md"),
+        "+++\ntitle = \"Extra\"\ndate = \"2024-01-01\"\nupdate = \"2024-01-01\"\nsummary = \"\"\npath = \"extra.md\"\nauthor = \"gerald\"\ncanonical = \"https://example.com/extra\"\npublished_at = 2024-01-01T08:00:00Z\n+++\n",
+    )
+    .expect("Failed to write article");
+    fs::write(
+        root.join("zone.toml"),
+        "[extra]\nexclude = [\"canonical\"]\n\n[extra.files.\"index.json\"]\ninclude = [\"canonical\"]\n",
+    )
+    .expect("Failed to write config");
+
+    let blog_dir = root.to_str().unwrap().to_string();
+    let config = Config::load(&blog_dir).expect("Failed to load config");
+    BlogProcessor::with_config(blog_dir, config)
+        .process_directory()
+        .expect("Failed to process directory");
+
+    let meta = read_articles(&tech_dir.join("meta.json"));
+    assert_eq!(meta[0].extra["author"], "gerald");
+    assert_eq!(meta[0].extra["published_at"], "2024-01-01T08:00:00Z");
+    assert!(!meta[0].extra.contains_key("canonical"));
+
+    let index: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(root.join("index.json")).expect("Failed to read index"),
+    )
+    .expect("Failed to parse index");
+    let entry = &index["list"][0];
+    assert_eq!(entry["canonical"], "https://example.com/extra");
+    assert!(entry.get("author").is_none());
+}
+
+#[test]
+fn test_author_profiles_and_indexes() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+    let tech_dir = root.join("tech");
+
+    fs::create_dir_all(&tech_dir).expect("Failed to create dir");
+    fs::write(
+        root.join("authors.toml"),
+        "[gerald]\nname = \"Gerald\"\nbio = \"Writes Rust\"\n\n[gerald.links]\ngithub = \"https://github.com/gerald\"\n\n[ada]\nname = \"Ada\"\n",
+    )
+    .expect("Failed to write authors");
+    fs::write(
+        tech_dir.join("pair.md"),
+        "+++\ntitle = \"Pair\"\ndate = \"2024-01-01\"\nupdate = \"2024-01-01\"\nsummary = \"\"\npath = \"pair.md\"\nauthors = [\"gerald\", \"ada\"]\n+++\n",
+    )
+    .expect("Failed to write article");
+    write_article(&tech_dir, "solo.md", "Solo", "2024-02-01");
+
+    BlogProcessor::new(root.to_str().unwrap().to_string())
+        .process_directory()
+        .expect("Failed to process directory");
+
+    let meta = read_articles(&tech_dir.join("meta.json"));
+    let pair = meta.iter().find(|a| a.title == "Pair").unwrap();
+    assert_eq!(pair.author_profiles.len(), 2);
+    assert_eq!(pair.author_profiles[0].name, "Gerald");
+
+    let authors: Vec<Author> = serde_json::from_str(
+        &fs::read_to_string(root.join("authors.json")).expect("Failed to read authors.json"),
+    )
+    .expect("Failed to parse authors.json");
+    assert_eq!(authors.len(), 2);
+    assert_eq!(authors[0].id, "ada");
+
+    let gerald: AuthorIndex = serde_json::from_str(
+        &fs::read_to_string(root.join("authors/gerald.json")).expect("Failed to read index"),
+    )
+    .expect("Failed to parse index");
+    assert_eq!(gerald.author.bio.as_deref(), Some("Writes Rust"));
+    assert_eq!(gerald.list.len(), 1);
+    assert_eq!(gerald.list[0].title, "Pair");
+}
+
+#[test]
+fn test_unknown_author_is_rejected() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+    let tech_dir = root.join("tech");
+
+    fs::create_dir_all(&tech_dir).expect("Failed to create dir");
+    fs::write(root.join("authors.toml"), "[gerald]\nname = \"Gerald\"\n")
+        .expect("Failed to write authors");
+    fs::write(
+        tech_dir.join("typo.md"),
+        "+++\ntitle = \"Typo\"\ndate = \"2024-01-01\"\nupdate = \"2024-01-01\"\nsummary = \"\"\npath = \"typo.md\"\nauthors = [\"gerlad\"]\n+++\n",
+    )
+    .expect("Failed to write article");
+
+    let result = BlogProcessor::new(root.to_str().unwrap().to_string()).process_directory();
+    let error = result.expect_err("Unknown author should fail").to_string();
+    assert!(error.contains("unknown author 'gerlad'"));
+}
+
+#[test]
+fn test_errors_name_path_and_cause() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+    let processor = BlogProcessor::new(root.to_str().unwrap().to_string());
+
+    write_series_part(&root.join("tutorials"), "git-1.md", 1);
+    write_series_part(&root.join("tutorials"), "git-2.md", 1);
+    // The frontmatter `path` no longer matches the file.
+    fs::rename(
+        root.join("tutorials/git-2.md"),
+        root.join("tutorials/second.md"),
+    )
+    .expect("Failed to rename article");
+    match processor.process_directory() {
+        Err(ZoneError::Validation { path, message }) => {
+            assert_eq!(path, root.join("tutorials/second.md"));
+            let other = root.join("tutorials/git-1.md");
+            assert!(
+                message.ends_with(&format!("(also used by {})", other.display())),
+                "{}",
+                message
+            );
+        }
+        other => panic!("expected a validation error, got {:?}", other),
+    }
+
+    fs::write(root.join("authors.toml"), "[gerald\n").expect("Failed to write authors");
+    let error = processor.process_directory().unwrap_err();
+    assert!(matches!(error, ZoneError::Toml { .. }), "{:?}", error);
+    assert_eq!(error.path(), root.join("authors.toml"));
+    let cause = std::error::Error::source(&error).expect("TOML error as source");
+    assert!(zone_builder::error::chain(&error).ends_with(&cause.to_string()));
+
+    fs::remove_file(root.join("authors.toml")).expect("Failed to remove authors");
+    fs::remove_file(root.join("tutorials/second.md")).expect("Failed to remove article");
+    write_series_part(&root.join("tutorials"), "git-2.md", 2);
+    let config_path = temp_dir.path().join("ci.toml");
+    fs::write(&config_path, "[lint.rules]\nno-such-rule = \"off\"\n")
+        .expect("Failed to write config");
+    let config = Config::load_from(&config_path).expect("Failed to load config");
+    let error = BlogProcessor::with_config(root.to_str().unwrap().to_string(), config)
+        .lint()
+        .unwrap_err();
+    match error {
+        ZoneError::Config { path, message } => {
+            assert_eq!(path, config_path);
+            assert_eq!(message, "unknown lint rule 'no-such-rule'");
+        }
+        other => panic!("expected a configuration error, got {:?}", other),
+    }
+
+    let missing = root.join("tech/missing.md");
+    let error =
+        zone_builder::touch::touch(std::slice::from_ref(&missing), "2024-05-01").unwrap_err();
+    match error {
+        ZoneError::Io { path, source } => {
+            assert_eq!(path, missing);
+            assert_eq!(source.kind(), std::io::ErrorKind::NotFound);
+        }
+        other => panic!("expected an I/O error, got {:?}", other),
+    }
+}
+
+fn write_series_part(dir: &std::path::Path, file_name: &str, order: u32) {
+    fs::create_dir_all(dir).expect("Failed to create dir");
+    fs::write(
+        dir.join(file_name),
+        format!(
+            "+++\ntitle = \"{file_name}\"\ndate = \"2024-01-0{order}\"\nupdate = \"2024-01-0{order}\"\nsummary = \"\"\npath = \"{file_name}\"\nseries = \"Git\"\nseries_order = {order}\n+++\n"
+        ),
+    )
+    .expect("Failed to write article");
+}
+
+#[test]
+fn test_series_navigation() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+
+    write_series_part(&root.join("tutorials"), "git-1.md", 1);
+    write_series_part(&root.join("tech"), "git-2.md", 2);
+    write_series_part(&root.join("tutorials"), "git-3.md", 3);
+
+    BlogProcessor::new(root.to_str().unwrap().to_string())
+        .process_directory()
+        .expect("Failed to process directory");
+
+    let tutorials = read_articles(&root.join("tutorials/meta.json"));
+    let first = tutorials.iter().find(|a| a.path == "git-1.md").unwrap();
+    let series = first.series.as_ref().expect("series info");
+    assert_eq!((series.index, series.total), (1, 3));
+    assert!(series.prev.is_none());
+    let next = series.next.as_ref().unwrap();
+    assert_eq!(next.path, "git-2.md");
+    assert_eq!(next.collection.as_deref(), Some("tech"));
+
+    let entries: Vec<SeriesEntry> = serde_json::from_str(
+        &fs::read_to_string(root.join("series.json")).expect("Failed to read series.json"),
+    )
+    .expect("Failed to parse series.json");
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].name, "Git");
+    assert_eq!(entries[0].parts[2].link.path, "git-3.md");
+}
+
+#[test]
+fn test_series_order_errors() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+    let processor = BlogProcessor::new(root.to_str().unwrap().to_string());
+
+    write_series_part(&root.join("tutorials"), "git-1.md", 1);
+    write_series_part(&root.join("tutorials"), "git-3.md", 3);
+    let error = processor.process_directory().unwrap_err().to_string();
+    assert!(error.contains("missing series_order 2"), "{}", error);
+
+    write_series_part(&root.join("tutorials"), "git-3.md", 1);
+    let error = processor.process_directory().unwrap_err().to_string();
+    assert!(error.contains("duplicate series_order 1"), "{}", error);
+}
+
+#[test]
+fn test_chronological_prev_next() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+    let tech_dir = root.join("tech");
+
+    write_article(&tech_dir, "old.md", "Old", "2024-01-01");
+    write_article(&tech_dir, "b-same-day.md", "Same Day B", "2024-02-01");
+    write_article(&tech_dir, "a-same-day.md", "Same Day A", "2024-02-01");
+    write_article(&root.join("essays"), "essay.md", "Essay", "2024-01-15");
+    fs::write(root.join("zone.toml"), "[navigation]\nsite_wide = true\n")
+        .expect("Failed to write config");
+
+    let blog_dir = root.to_str().unwrap().to_string();
+    let config = Config::load(&blog_dir).expect("Failed to load config");
+    BlogProcessor::with_config(blog_dir, config)
+        .process_directory()
+        .expect("Failed to process directory");
+
+    let meta = read_articles(&tech_dir.join("meta.json"));
+    let order: Vec<&str> = meta.iter().map(|a| a.path.as_str()).collect();
+    assert_eq!(order, vec!["a-same-day.md", "b-same-day.md", "old.md"]);
+    assert!(meta[0].next.is_none());
+    assert_eq!(meta[0].prev.as_ref().unwrap().path, "b-same-day.md");
+    assert_eq!(meta[2].next.as_ref().unwrap().title, "Same Day B");
+    assert!(meta[2].prev.is_none());
+
+    let index: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(root.join("index.json")).expect("Failed to read index"),
+    )
+    .expect("Failed to parse index");
+    let essay = &index["list"][2];
+    assert_eq!(essay["path"], "essay.md");
+    assert_eq!(essay["site_next"]["path"], "b-same-day.md");
+    assert_eq!(essay["site_prev"]["path"], "old.md");
+    assert!(essay.get("prev").is_none());
+}
+
+#[test]
+fn test_multilingual_translations() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+    let tech_dir = root.join("tech");
+
+    write_article(&tech_dir, "post.md", "文章", "2024-01-01");
+    write_article(&tech_dir, "post.en.md", "Post", "2024-01-01");
+    write_article(&tech_dir, "only-zh.md", "仅中文", "2024-02-01");
+    fs::write(
+        root.join("zone.toml"),
+        "[i18n]\ndefault_language = \"zh\"\nlanguages = [\"zh\", \"en\"]\n",
+    )
+    .expect("Failed to write config");
+
+    let blog_dir = root.to_str().unwrap().to_string();
+    let config = Config::load(&blog_dir).expect("Failed to load config");
+    BlogProcessor::with_config(blog_dir, config)
+        .process_directory()
+        .expect("Failed to process directory");
+
+    let english = read_articles(&tech_dir.join("meta.en.json"));
+    assert_eq!(english.len(), 1);
+    assert_eq!(english[0].lang.as_deref(), Some("en"));
+    assert_eq!(english[0].translation_key.as_deref(), Some("tech/post"));
+    assert_eq!(english[0].alternates.len(), 1);
+    assert_eq!(english[0].alternates[0].lang, "zh");
+    assert_eq!(english[0].alternates[0].link.path, "post.md");
+
+    let chinese = read_articles(&tech_dir.join("meta.zh.json"));
+    assert_eq!(chinese.len(), 2);
+    assert_eq!(chinese[1].prev, None);
+    assert_eq!(chinese[1].next.as_ref().unwrap().path, "only-zh.md");
+
+    for file in [
+        "index.en.json",
+        "index.zh.json",
+        "latest.en.json",
+        "recommended.zh.json",
+    ] {
+        assert!(root.join(file).exists(), "{} should exist", file);
+    }
+    assert_eq!(read_articles(&tech_dir.join("meta.json")).len(), 3);
+}
+
+#[test]
+fn test_incremental_build_cache() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+    let tech_dir = root.join("tech");
+    let article_path = tech_dir.join("cached.md");
+    let meta_path = tech_dir.join("meta.json");
+
+    write_article(&tech_dir, "cached.md", "Title A", "2024-01-01");
+    let mut processor = BlogProcessor::new(root.to_str().unwrap().to_string());
+    processor
+        .process_directory()
+        .expect("Failed to process directory");
+    assert!(root.join(".zone-cache").exists());
+
+    // Same size and modification time: the cached parse result is reused.
+    let stamp = fs::metadata(&article_path).unwrap().modified().unwrap();
+    write_article(&tech_dir, "cached.md", "Title B", "2024-01-01");
+    fs::File::options()
+        .write(true)
+        .open(&article_path)
+        .and_then(|file| file.set_modified(stamp))
+        .expect("Failed to reset mtime");
+
+    let old_time = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000);
+    fs::File::options()
+        .write(true)
+        .open(&meta_path)
+        .and_then(|file| file.set_modified(old_time))
+        .expect("Failed to set mtime");
+
+    processor
+        .process_directory()
+        .expect("Failed to process directory");
+    assert_eq!(read_articles(&meta_path)[0].title, "Title A");
+    assert_eq!(
+        fs::metadata(&meta_path).unwrap().modified().unwrap(),
+        old_time
+    );
+
+    processor.force = true;
+    processor
+        .process_directory()
+        .expect("Failed to process directory");
+    assert_eq!(read_articles(&meta_path)[0].title, "Title B");
+}
+
+#[test]
+fn test_unchanged_outputs_are_not_rewritten() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+
+    write_article(&root.join("tech"), "one.md", "One", "2024-01-01");
+    let processor = BlogProcessor::new(root.to_str().unwrap().to_string());
+
+    let first = processor
+        .process_directory()
+        .expect("Failed to process directory");
+    assert_eq!(first.unchanged, 0);
+    assert!(first.written >= 5);
+
+    let second = processor
+        .process_directory()
+        .expect("Failed to process directory");
+    assert_eq!(second.written, 0);
+    assert_eq!(second.unchanged, first.written);
+
+    write_article(&root.join("tech"), "two.md", "Two", "2024-02-01");
+    let third = processor
+        .process_directory()
+        .expect("Failed to process directory");
+    assert!(third.written > 0);
+    assert!(third.unchanged > 0, "notebooks.json should be unchanged");
+}
+
+#[test]
+fn test_failed_build_keeps_previous_outputs() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+    let tech_dir = root.join("tech");
+
+    write_article(&tech_dir, "good.md", "Good", "2024-01-01");
+    let mut config = Config::default();
+    config.render.html = true;
+    let processor = BlogProcessor::with_config(root.to_str().unwrap().to_string(), config);
+    processor
+        .process_directory()
+        .expect("Failed to process directory");
+    let index_before = fs::read_to_string(root.join("index.json")).unwrap();
+
+    write_article(&tech_dir, "newer.md", "Newer", "2024-02-01");
+    fs::write(
+        tech_dir.join("zz-broken.md"),
+        "+++\ntitle = \"Broken\"\ndate = \"2024-03-01\"\nupdate = \"2024-03-01\"\nsummary = \"\"\npath = \"broken.md\"\nauthors = [\"nobody\"]\n+++\n",
+    )
+    .expect("Failed to write article");
+
+    assert!(processor.process_directory().is_err());
+    assert_eq!(
+        fs::read_to_string(root.join("index.json")).unwrap(),
+        index_before
+    );
+    assert_eq!(read_articles(&tech_dir.join("meta.json")).len(), 1);
+    assert!(tech_dir.join("good.html").exists());
+    assert!(!tech_dir.join("newer.html").exists());
+
+    let leftovers: Vec<_> = walkdir::WalkDir::new(root)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name().to_string_lossy().ends_with(".tmp"))
+        .collect();
+    assert!(
+        leftovers.is_empty(),
+        "temporary files left: {:?}",
+        leftovers
+    );
+}
+
+#[test]
+fn test_parallel_parsing_is_deterministic() {
+    let outputs: Vec<Vec<(String, String)>> = [1, 4]
+        .iter()
+        .map(|jobs| {
+            let temp_dir = TempDir::new().expect("Failed to create temp dir");
+            let root = temp_dir.path();
+            for i in 0..40 {
+                let collection = ["tech", "essays", "tech/rust"][i % 3];
+                write_article(
+                    &root.join(collection),
+                    &format!("post-{:02}.md", i),
+                    &format!("Post {}", i),
+                    &format!("2024-01-{:02}", i % 5 + 1),
+                );
+            }
+
+            let mut processor = BlogProcessor::new(root.to_str().unwrap().to_string());
+            processor.jobs = *jobs;
+            processor
+                .process_directory()
+                .expect("Failed to process directory");
+
+            let mut files: Vec<(String, String)> = walkdir::WalkDir::new(root)
+                .sort_by_file_name()
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+                .map(|e| {
+                    let relative = e.path().strip_prefix(root).unwrap().display().to_string();
+                    (relative, fs::read_to_string(e.path()).unwrap())
+                })
+                .collect();
+            files.sort();
+            files
+        })
+        .collect();
+
+    assert!(!outputs[0].is_empty());
+    assert_eq!(outputs[0], outputs[1]);
+}
+
+#[test]
+fn test_watch_ignores_generated_files() {
+    use std::path::Path;
+    use zone_builder::watch::is_watched;
+
+    assert!(is_watched(Path::new("blog/rust/post.md")));
+    assert!(is_watched(Path::new("blog/rust/name.txt")));
+    assert!(is_watched(Path::new("blog/zone.toml")));
+    assert!(is_watched(Path::new("blog/authors.toml")));
+
+    assert!(!is_watched(Path::new("blog/rust/meta.json")));
+    assert!(!is_watched(Path::new("blog/index.json")));
+    assert!(!is_watched(Path::new("blog/rust/post.html")));
+    assert!(!is_watched(Path::new("blog/.index.json.42.tmp")));
+    assert!(!is_watched(Path::new("blog/.zone-cache")));
+    assert!(!is_watched(Path::new(
+        "blog/assets/diagram.0123456789abcdef.png"
+    )));
+}
+
+#[test]
+fn test_preview_server_serves_outputs_with_cors_and_reload_events() {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpStream;
+    use zone_builder::serve::{EVENTS_PATH, PreviewServer};
+
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+    write_article(&root.join("rust"), "post.md", "Post", "2024-01-01");
+    fs::write(root.join(".zone-cache"), "{}").unwrap();
+    BlogProcessor::new(root.to_str().unwrap().to_string())
+        .process_directory()
+        .expect("Failed to process directory");
+
+    let invalid = PreviewServer::bind("127.0.0.1:0", root, "http://a\r\nSet-Cookie: x=1");
+    assert!(matches!(invalid, Err(ZoneError::Config { .. })));
+
+    let mut server =
+        PreviewServer::bind("127.0.0.1:0", root, "http://localhost:5173").expect("Failed to bind");
+    let addr = server.addr().expect("Expected an IP address");
+    server.spawn();
+
+    let get = |path: &str| {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+            path
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    };
+
+    let response = get("/rust/meta.json");
+    assert!(response.starts_with("HTTP/1.1 200"));
+    assert!(response.contains("Content-Type: application/json"));
+    assert!(response.contains("Access-Control-Allow-Origin: http://localhost:5173"));
+    assert!(response.contains("\"title\":\"Post\"") || response.contains("\"title\": \"Post\""));
+
+    assert!(get("/.zone-cache").starts_with("HTTP/1.1 404"));
+    assert!(get("/../etc/passwd").starts_with("HTTP/1.1 404"));
+
+    let mut events = TcpStream::connect(addr).unwrap();
+    write!(
+        events,
+        "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n",
+        EVENTS_PATH
+    )
+    .unwrap();
+    let mut reader = BufReader::new(events);
+    let mut line = String::new();
+    while line != ": connected\n" {
+        line.clear();
+        reader.read_line(&mut line).unwrap();
+    }
+
+    server.notify_reload();
+    line.clear();
+    while line.trim().is_empty() {
+        line.clear();
+        reader.read_line(&mut line).unwrap();
+    }
+    assert_eq!(line, "event: reload\n");
+}
+
+#[test]
+fn test_check_and_articles_write_nothing() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+    write_article(&root.join("rust"), "older.md", "Older", "2024-01-01");
+    write_article(&root.join("rust"), "newer.md", "Newer", "2024-02-01");
+
+    let processor = BlogProcessor::new(root.to_str().unwrap().to_string());
+    let report = processor.check().expect("Check failed");
+    assert!(report.summary.written > 0);
+    assert_eq!(report.summary.unchanged, 0);
+
+    let articles = processor.articles().expect("Failed to list articles");
+    let titles: Vec<&str> = articles.iter().map(|a| a.title.as_str()).collect();
+    assert_eq!(titles, ["Newer", "Older"]);
+
+    assert!(!root.join("index.json").exists());
+    assert!(!root.join("rust/meta.json").exists());
+    assert!(!root.join(".zone-cache").exists());
+
+    // New responsive variants of an already copied image are planned, not
+    // written.
+    fs::create_dir_all(root.join("rust/images")).unwrap();
+    image::RgbImage::new(600, 300)
+        .save(root.join("rust/images/pic.png"))
+        .unwrap();
+    fs::write(
+        root.join("rust/photo.md"),
+        "+++\ntitle = \"Photo\"\ndate = \"2024-03-01\"\nupdate = \"2024-03-01\"\nsummary = \"\"\npath = \"photo.md\"\n+++\n\n![Pic](images/pic.png)\n",
+    )
+    .unwrap();
+    processor
+        .process_directory()
+        .expect("Failed to process directory");
+    let assets_before: Vec<_> = fs::read_dir(root.join("assets"))
+        .unwrap()
+        .map(|e| e.unwrap().file_name())
+        .collect();
+    assert!(
+        assets_before
+            .iter()
+            .any(|n| n.to_string_lossy().ends_with(".480w.png"))
+    );
+
+    fs::write(root.join("zone.toml"), "[images]\nwidths = [300]\n").unwrap();
+    let config = Config::load(root.to_str().unwrap()).unwrap();
+    let report = BlogProcessor::with_config(root.to_str().unwrap().to_string(), config)
+        .check()
+        .expect("Check failed");
+    let mut assets_after: Vec<_> = fs::read_dir(root.join("assets"))
+        .unwrap()
+        .map(|e| e.unwrap().file_name())
+        .collect();
+    assets_after.sort();
+    let mut expected = assets_before.clone();
+    expected.sort();
+    assert_eq!(assets_after, expected);
+
+    let created: Vec<String> = report
+        .changes
+        .iter()
+        .filter(|c| c.kind == ChangeKind::Created)
+        .map(|c| c.path.file_name().unwrap().to_string_lossy().to_string())
+        .collect();
+    assert!(
+        created.iter().any(|n| n.ends_with(".300w.png")),
+        "{:?}",
+        created
+    );
+    assert!(
+        created.iter().any(|n| n.ends_with(".300w.webp")),
+        "{:?}",
+        created
+    );
+}
+
+#[test]
+fn test_output_dir_and_clean() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let blog = temp_dir.path().join("blog");
+    let out = temp_dir.path().join("public");
+    write_article(&blog.join("rust"), "post.md", "Post", "2024-01-01");
+    fs::create_dir_all(blog.join("rust/images")).unwrap();
+    fs::write(blog.join("rust/images/pic.png"), png_header(4, 3)).unwrap();
+    fs::write(
+        blog.join("rust/photo.md"),
+        "+++\ntitle = \"Photo\"\ndate = \"2024-01-02\"\nupdate = \"2024-01-02\"\nsummary = \"\"\npath = \"photo.md\"\n+++\n\n![Pic](images/pic.png)\n",
+    )
+    .unwrap();
+    fs::write(blog.join("notes.txt"), "keep me").unwrap();
+
+    let mut processor = BlogProcessor::new(blog.to_str().unwrap().to_string());
+    processor.output_dir = Some(out.clone());
+    processor
+        .process_directory()
+        .expect("Failed to process directory");
+
+    assert!(out.join("index.json").exists());
+    assert!(out.join("rust/meta.json").exists());
+    assert!(out.join("assets/manifest.json").exists());
+    assert!(!blog.join("index.json").exists());
+    assert!(!blog.join("rust/meta.json").exists());
+
+    // Without `render.html` an `.html` next to an article is hand-written,
+    // and only collections have a generated `meta.json`.
+    fs::write(out.join("rust/post.html"), "<p>hand-written</p>").unwrap();
+    fs::create_dir_all(out.join("rust/images")).unwrap();
+    fs::write(out.join("rust/images/meta.json"), "{}").unwrap();
+
+    let removed = processor.clean().expect("Failed to clean");
+    assert!(removed.contains(&out.join("index.json")));
+    assert!(removed.contains(&blog.join(".zone-cache")));
+    assert!(!out.join("rust/meta.json").exists());
+    assert!(!out.join("assets").exists());
+    assert!(blog.join("rust/post.md").exists());
+    assert!(blog.join("rust/images/pic.png").exists());
+    assert!(blog.join("notes.txt").exists());
+    assert!(out.join("rust/post.html").exists());
+    assert!(out.join("rust/images/meta.json").exists());
+}
+
+#[test]
+fn test_new_article_scaffold() {
+    use zone_builder::scaffold::{new_article, slugify};
+
+    assert_eq!(slugify("Hello, World!"), "hello-world");
+    assert_eq!(slugify("  Rust & WebAssembly 2 "), "rust-webassembly-2");
+    assert_eq!(slugify("你好 World"), "你好-world");
+
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let path = new_article(temp_dir.path(), "tech/rust", "Say \"Hi\"", "2024-03-01")
+        .expect("Failed to create article");
+    assert_eq!(path, temp_dir.path().join("tech/rust/say-hi.md"));
+
+    let content = fs::read_to_string(&path).unwrap();
+    let (article, _) = Article::extract_frontmatter(&content).expect("Invalid frontmatter");
+    assert_eq!(article.title, "Say \"Hi\"");
+    assert_eq!(article.date, "2024-03-01");
+    assert_eq!(article.path, "say-hi.md");
+    assert_eq!(article.collection.as_deref(), Some("tech/rust"));
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("tech/name.txt")).unwrap(),
+        "Tech\n"
+    );
+    assert!(temp_dir.path().join("tech/rust/name.txt").exists());
+
+    assert!(new_article(temp_dir.path(), "tech/rust", "Say Hi", "2024-03-02").is_err());
+    assert!(new_article(temp_dir.path(), "../outside", "Escape", "2024-03-02").is_err());
+}
+
+#[test]
+fn test_new_article_uses_closest_archetype() {
+    use zone_builder::scaffold::new_article;
+
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+    fs::create_dir_all(root.join("archetypes")).unwrap();
+    fs::write(
+        root.join("archetypes/tech.md"),
+        "+++\n# Tech post\ntitle = \"{{title}}\"\ndate = \"{{date}}\"\nupdate = \"{{date}}\"\nsummary = \"TODO\"\npath = \"{{path}}\"\ntags = [\"tech\"]\n+++\n\n## {{title}}\n",
+    )
+    .unwrap();
+    fs::write(
+        root.join("archetypes/default.md"),
+        "+++\ntitle = \"{{title}}\"\n+++\n",
+    )
+    .unwrap();
+
+    let path = new_article(root, "tech/rust", "Quote \" Me", "2024-03-01").unwrap();
+    let content = fs::read_to_string(&path).unwrap();
+    assert!(content.starts_with("+++\n# Tech post\ntitle = \"Quote \\\" Me\"\n"));
+    assert!(content.ends_with("## Quote \\\" Me\n"));
+    let (article, _) = Article::extract_frontmatter(&content).unwrap();
+    assert_eq!(article.path, "quote-me.md");
+    assert_eq!(article.summary, "TODO");
+
+    let error = new_article(root, "essays", "Broken", "2024-03-01").unwrap_err();
+    assert!(error.to_string().contains("default.md"));
+    assert!(!root.join("essays/broken.md").exists());
+
+    BlogProcessor::new(root.to_str().unwrap().to_string())
+        .process_directory()
+        .expect("Failed to process directory");
+    let titles: Vec<String> = read_articles(&root.join("latest.json"))
+        .into_iter()
+        .map(|a| a.title)
+        .collect();
+    assert_eq!(titles, ["Quote \" Me"]);
+    assert!(!root.join("archetypes/meta.json").exists());
+}
+
+#[test]
+fn test_check_reports_out_of_date_outputs() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+    write_article(&root.join("rust"), "post.md", "Post", "2024-01-01");
+    write_article(&root.join("go"), "gopher.md", "Gopher", "2024-01-02");
+    // Hand-written, outside any collection: never produced by a build.
+    fs::create_dir_all(root.join("static")).unwrap();
+    fs::write(root.join("static/meta.json"), "{\"kept\": true}").unwrap();
+
+    let processor = BlogProcessor::new(root.to_str().unwrap().to_string());
+    processor
+        .process_directory()
+        .expect("Failed to process directory");
+
+    let report = processor.check().expect("Check failed");
+    assert!(report.changes.is_empty());
+    assert_eq!(report.out_of_date().count(), 0);
+
+    write_article(&root.join("rust"), "post.md", "Renamed", "2024-01-01");
+    fs::remove_file(root.join("go/gopher.md")).unwrap();
+    write_article(&root.join("zig"), "new.md", "New", "2024-01-03");
+    let index_before = fs::read_to_string(root.join("index.json")).unwrap();
+
+    let report = processor.check().expect("Check failed");
+    let kind_of = |path: &str| {
+        report
+            .changes
+            .iter()
+            .find(|c| c.path == root.join(path))
+            .map(|c| c.kind)
+    };
+    assert_eq!(kind_of("rust/meta.json"), Some(ChangeKind::Modified));
+    assert_eq!(kind_of("index.json"), Some(ChangeKind::Modified));
+    assert_eq!(kind_of("zig/meta.json"), Some(ChangeKind::Created));
+    assert_eq!(kind_of("go/meta.json"), Some(ChangeKind::Deleted));
+    assert_eq!(kind_of("static/meta.json"), None);
+    assert!(report.out_of_date().count() >= 4);
+
+    let diff = report
+        .changes
+        .iter()
+        .find(|c| c.path == root.join("rust/meta.json"))
+        .unwrap()
+        .unified_diff(root);
+    assert!(diff.starts_with("--- a/rust/meta.json\n+++ b/rust/meta.json\n"));
+    assert!(diff.contains("-    \"title\": \"Post\","));
+    assert!(diff.contains("+    \"title\": \"Renamed\","));
+
+    assert_eq!(
+        fs::read_to_string(root.join("index.json")).unwrap(),
+        index_before
+    );
+    assert!(!root.join("zig/meta.json").exists());
+    assert!(root.join("go/meta.json").exists());
+
+    processor
+        .process_directory()
+        .expect("Failed to process directory");
+    assert!(!root.join("go/meta.json").exists());
+    assert!(root.join("static/meta.json").exists());
+    let report = processor.check().expect("Check failed");
+    assert!(report.changes.is_empty(), "{:?}", report.changes);
+
+    processor.clean().expect("Failed to clean");
+    assert!(!root.join("rust/meta.json").exists());
+    assert!(root.join("static/meta.json").exists());
+}
+
+#[test]
+fn test_set_update_date_preserves_frontmatter() {
+    use zone_builder::touch::set_update_date;
+
+    let content = "+++\ntitle = \"Post\"\ndate = \"2024-01-01\"\nupdate   =   \"2024-01-02\"  # bumped by hand\nsummary = 'Keep update = \"x\" here'\npath = \"post.md\"\n\n[extra]\nupdate = \"nested\"\n+++\n\nupdate = \"body\"\n";
+    assert_eq!(
+        set_update_date(content, "2024-05-06").unwrap(),
+        content.replacen("\"2024-01-02\"", "\"2024-05-06\"", 1)
+    );
+
+    let single = "+++\ntitle = 'Post'\ndate = '2024-01-01'\nupdate = '2024-01-02'\n+++\n";
+    assert_eq!(
+        set_update_date(single, "2024-05-06").unwrap(),
+        "+++\ntitle = 'Post'\ndate = '2024-01-01'\nupdate = '2024-05-06'\n+++\n"
+    );
+
+    let bare = "+++\ntitle = \"Post\"\ndate = 2024-01-01\nupdate = 2024-01-02#c\n+++\n";
+    assert_eq!(
+        set_update_date(bare, "2024-05-06").unwrap(),
+        "+++\ntitle = \"Post\"\ndate = 2024-01-01\nupdate = 2024-05-06#c\n+++\n"
+    );
+
+    let missing = "+++\ntitle = \"Post\"\ndate = \"2024-01-01\"\nsummary = \"\"\n+++\nBody\n";
+    assert_eq!(
+        set_update_date(missing, "2024-05-06").unwrap(),
+        "+++\ntitle = \"Post\"\ndate = \"2024-01-01\"\nupdate = \"2024-05-06\"\nsummary = \"\"\n+++\nBody\n"
+    );
+
+    assert!(set_update_date("No frontmatter", "2024-05-06").is_none());
+}
+
+#[test]
+fn test_touch_changed_since_build() {
+    use zone_builder::touch::{changed_since_build, touch};
+
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+    let blog_dir = root.to_str().unwrap().to_string();
+    write_article(&root.join("rust"), "edited.md", "Edited", "2024-01-01");
+    write_article(
+        &root.join("rust"),
+        "untouched.md",
+        "Untouched",
+        "2024-01-01",
+    );
+
+    assert!(changed_since_build(&blog_dir).is_err());
+
+    let processor = BlogProcessor::new(blog_dir.clone());
+    processor
+        .process_directory()
+        .expect("Failed to process directory");
+
+    let edited = root.join("rust/edited.md");
+    let content = fs::read_to_string(&edited).unwrap();
+    fs::write(&edited, content.replace("Body of", "New body of")).unwrap();
+    write_article(&root.join("rust"), "added.md", "Added", "2024-01-01");
+
+    let changed = changed_since_build(&blog_dir).unwrap();
+    assert_eq!(changed, std::slice::from_ref(&edited));
+
+    assert_eq!(touch(&changed, "2024-06-01").unwrap(), changed);
+    assert!(touch(&changed, "2024-06-01").unwrap().is_empty());
+
+    processor
+        .process_directory()
+        .expect("Failed to process directory");
+    let articles = read_articles(&root.join("rust/meta.json"));
+    let edited_article = articles.iter().find(|a| a.title == "Edited").unwrap();
+    assert_eq!(edited_article.update, "2024-06-01");
+    assert!(changed_since_build(&blog_dir).unwrap().is_empty());
+}
+
+#[test]
+fn test_dates_from_git_history() {
+    use std::process::Command;
+
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+    let git = |args: &[&str], date: &str| {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(root)
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .env("GIT_AUTHOR_DATE", date)
+            .env("GIT_COMMITTER_DATE", date)
+            .status()
+            .expect("Failed to run git");
+        assert!(status.success());
+    };
+    let undated = |title: &str, file_name: &str| {
+        format!("+++\ntitle = \"{title}\"\nsummary = \"\"\npath = \"{file_name}\"\n+++\n\nBody\n")
+    };
+
+    fs::create_dir_all(root.join("rust")).unwrap();
+    fs::write(root.join("zone.toml"), "[dates]\nfrom_git = true\n").unwrap();
+    fs::write(
+        root.join("rust/history.md"),
+        undated("History", "history.md"),
+    )
+    .unwrap();
+    write_article(&root.join("rust"), "pinned.md", "Pinned", "2020-01-01");
+    git(&["init", "-q"], "2023-01-05T10:00:00");
+    git(&["add", "."], "2023-01-05T10:00:00");
+    git(&["commit", "-q", "-m", "Add"], "2023-01-05T10:00:00");
+
+    fs::write(
+        root.join("rust/history.md"),
+        undated("History", "history.md") + "More\n",
+    )
+    .unwrap();
+    git(&["commit", "-q", "-am", "Edit"], "2023-02-10T10:00:00");
+    fs::write(root.join("rust/draft.md"), undated("Draft", "draft.md")).unwrap();
+
+    let config = Config::load(root.to_str().unwrap()).unwrap();
+    BlogProcessor::with_config(root.to_str().unwrap().to_string(), config)
+        .process_directory()
+        .expect("Failed to process directory");
+
+    let articles = read_articles(&root.join("rust/meta.json"));
+    assert_eq!(articles.len(), 2);
+    let history = articles.iter().find(|a| a.title == "History").unwrap();
+    assert_eq!(history.date, "2023-01-05");
+    assert_eq!(history.update, "2023-02-10");
+    let pinned = articles.iter().find(|a| a.title == "Pinned").unwrap();
+    assert_eq!(pinned.date, "2020-01-01");
+    assert_eq!(pinned.update, "2020-01-01");
+}
+
+#[test]
+fn test_lint_rules_with_options_and_suppression() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+
+    fs::create_dir_all(root.join("tech")).unwrap();
+    fs::write(
+        root.join("zone.toml"),
+        "[lint.rules]\nmissing-cover = \"off\"\ntitle-case = \"error\"\n\n[lint.rules.disallowed-words]\nwords = [\"simply\"]\n",
+    )
+    .unwrap();
+    fs::write(
+        root.join("tech/style.md"),
+        "+++\ntitle = \"notes on style\"\ndate = \"2024-02-01\"\nupdate = \"2024-01-01\"\nsummary = \"A summary that is long enough.\"\npath = \"style.md\"\n+++\n\n## Start\n\n#### Too deep\n\nIt is Simply done. \n",
+    )
+    .unwrap();
+    fs::write(
+        root.join("tech/quiet.md"),
+        "+++\ntitle = \"quiet\"\ndate = \"2024-01-01\"\nupdate = \"2024-01-01\"\nsummary = \"Short\"\npath = \"quiet.md\"\nlint_ignore = [\"title-case\", \"summary-length\"]\n+++\n\nBody\n",
+    )
+    .unwrap();
+
+    let config = Config::load(root.to_str().unwrap()).unwrap();
+    let processor = BlogProcessor::with_config(root.to_str().unwrap().to_string(), config);
+    let diagnostics = processor.lint().expect("Failed to lint");
+
+    let found: Vec<_> = diagnostics
+        .iter()
+        .map(|d| {
+            (
+                d.file.as_str(),
+                d.rule.as_str(),
+                d.line,
+                d.column,
+                d.severity,
+            )
+        })
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            (
+                "tech/style.md",
+                "title-case",
+                Some(2),
+                Some(1),
+                Severity::Error
+            ),
+            (
+                "tech/style.md",
+                "update-before-date",
+                Some(4),
+                Some(1),
+                Severity::Error
+            ),
+            (
+                "tech/style.md",
+                "heading-hierarchy",
+                Some(11),
+                Some(1),
+                Severity::Warning
+            ),
+            (
+                "tech/style.md",
+                "disallowed-words",
+                Some(13),
+                Some(7),
+                Severity::Warning
+            ),
+            (
+                "tech/style.md",
+                "trailing-whitespace",
+                Some(13),
+                Some(19),
+                Severity::Warning
+            ),
+        ]
+    );
+
+    processor.process_directory().unwrap();
+    let exported = read_articles(&root.join("tech/meta.json"));
+    assert!(
+        exported
+            .iter()
+            .all(|a| !a.extra.contains_key("lint_ignore"))
+    );
+
+    fs::write(
+        root.join("zone.toml"),
+        "[lint.rules]\nno-such-rule = \"error\"\n",
+    )
+    .unwrap();
+    let config = Config::load(root.to_str().unwrap()).unwrap();
+    let processor = BlogProcessor::with_config(root.to_str().unwrap().to_string(), config);
+    assert!(processor.check().is_err());
+}
+
+#[test]
+fn test_custom_lint_rule() {
+    use zone_builder::lint::{Document, Finding, Rule, RuleOptions};
+
+    struct NoTodo;
+
+    impl Rule for NoTodo {
+        fn id(&self) -> &'static str {
+            "no-todo"
+        }
+
+        fn default_severity(&self) -> Severity {
+            Severity::Error
+        }
+
+        fn check(&self, doc: &Document, _options: &RuleOptions) -> Vec<Finding> {
+            doc.body
+                .find("TODO")
+                .map(|i| Finding::new(Some(doc.offset_of(doc.body) + i), "unfinished article"))
+                .into_iter()
+                .collect()
+        }
+    }
+
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+    fs::create_dir_all(root.join("tech")).unwrap();
+    fs::write(
+        root.join("tech/draft.md"),
+        "+++\ntitle = \"Draft\"\ndate = \"2024-01-01\"\nupdate = \"2024-01-01\"\nsummary = \"A summary that is long enough.\"\npath = \"draft.md\"\ncover = \"missing.png\"\n+++\n\nTODO write\n",
+    )
+    .unwrap();
+
+    let mut processor = BlogProcessor::new(root.to_str().unwrap().to_string());
+    processor.linter.register(NoTodo);
+    let report = processor.check().expect("Failed to check");
+
+    let errors: Vec<String> = report.lint_errors().map(|d| d.to_string()).collect();
+    assert_eq!(
+        errors,
+        vec!["tech/draft.md:10:1: error[no-todo]: unfinished article"]
+    );
+}
+
+#[test]
+fn test_check_reports_broken_links() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+
+    fs::write(
+        root.join("zone.toml"),
+        "[lint.rules]\nmissing-cover = \"off\"\n",
+    )
+    .unwrap();
+    write_article(&root.join("rust"), "async.md", "Async", "2024-01-01");
+    fs::write(
+        root.join("rust/async.md"),
+        fs::read_to_string(root.join("rust/async.md")).unwrap() + "\n## Deep Dive\n",
+    )
+    .unwrap();
+    fs::create_dir_all(root.join("tech")).unwrap();
+    fs::write(
+        root.join("tech/intro.md"),
+        "+++\ntitle = \"Intro\"\ndate = \"2024-01-01\"\nupdate = \"2024-01-01\"\nsummary = \"A summary that is long enough.\"\npath = \"intro.md\"\n+++\n\n## Setup\n\n[a](#setup) [b](../rust/async#deep-dive) [c](/rust/async.html)\n[d](#teardown) [e](../rust/sync.md) [f](https://example.com/x)\n\n![g](images/missing.png)\n",
+    )
+    .unwrap();
+
+    let config = Config::load(root.to_str().unwrap()).unwrap();
+    let processor = BlogProcessor::with_config(root.to_str().unwrap().to_string(), config);
+    let report = processor.check().expect("Failed to check");
+
+    let broken: Vec<String> = report.lint_errors().map(|d| d.to_string()).collect();
+    assert_eq!(
+        broken,
+        vec![
+            "tech/intro.md:12:1: error[broken-link]: no heading #teardown in tech/intro.md",
+            "tech/intro.md:12:16: error[broken-link]: broken link ../rust/sync.md",
+            "tech/intro.md:14:1: error[broken-link]: missing image images/missing.png",
+        ]
+    );
+    assert_eq!(report.external_links.len(), 1);
+    assert_eq!(report.external_links[0].url, "https://example.com/x");
+    assert_eq!(
+        (
+            report.external_links[0].line,
+            report.external_links[0].column
+        ),
+        (12, 37)
+    );
+}
+
+#[test]
+fn test_build_report_and_sarif() {
+    use zone_builder::output::WriteOutcome;
+    use zone_builder::report::FileStatus;
+
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+
+    fs::write(
+        root.join("zone.toml"),
+        "[lint.rules]\nmissing-cover = \"off\"\nsummary-length = \"off\"\n",
+    )
+    .unwrap();
+    write_article(&root.join("tech"), "intro.md", "Intro", "2024-01-01");
+    fs::write(
+        root.join("tech/intro.md"),
+        fs::read_to_string(root.join("tech/intro.md")).unwrap() + "\n![x](images/gone.png)\n",
+    )
+    .unwrap();
+    fs::write(root.join("tech/notes.md"), "No frontmatter here.\n").unwrap();
+    fs::write(
+        root.join("tech/typo.md"),
+        "+++\ntitle = Typo\ndate = \"2024-01-02\"\n+++\n\nBody.\n",
+    )
+    .unwrap();
+
+    let config = Config::load(root.to_str().unwrap()).unwrap();
+    let processor = BlogProcessor::with_config(root.to_str().unwrap().to_string(), config);
+    let report = processor.build_report();
+
+    assert!(report.success);
+    let files: Vec<_> = report
+        .files
+        .iter()
+        .map(|f| (f.file.as_str(), f.status))
+        .collect();
+    assert_eq!(
+        files,
+        vec![
+            ("tech/intro.md", FileStatus::Built),
+            ("tech/notes.md", FileStatus::Skipped),
+            ("tech/typo.md", FileStatus::Skipped)
+        ]
+    );
+    assert_eq!(report.files[0].metadata.as_ref().unwrap().title, "Intro");
+    assert_eq!(
+        report.files[1].error.as_deref(),
+        Some("no valid frontmatter")
+    );
+    let typo_error = report.files[2].error.as_deref().unwrap();
+    assert!(
+        typo_error.starts_with("invalid frontmatter at line 2, column 9: "),
+        "{}",
+        typo_error
+    );
+    assert!(
+        report
+            .outputs
+            .iter()
+            .any(|o| o.path == "tech/meta.json" && o.outcome == WriteOutcome::Written)
+    );
+    let phases: Vec<_> = report.timings.iter().map(|t| t.phase.as_str()).collect();
+    assert_eq!(
+        phases,
+        vec!["load", "parse", "articles", "outputs", "commit", "lint"]
+    );
+    let rules: Vec<_> = report.diagnostics.iter().map(|d| d.rule.as_str()).collect();
+    assert_eq!(
+        rules,
+        vec!["missing-image", "broken-link", "invalid-frontmatter"]
+    );
+
+    let sarif = report.to_sarif(std::path::Path::new("./blog"));
+    let results = sarif["runs"][0]["results"].as_array().unwrap();
+    assert_eq!(sarif["version"], "2.1.0");
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[1]["level"], "error");
+    let location = &results[1]["locations"][0]["physicalLocation"];
+    assert_eq!(location["artifactLocation"]["uri"], "blog/tech/intro.md");
+    assert_eq!(location["region"]["startLine"], 11);
+
+    let rebuilt = processor.build_report();
+    assert!(
+        rebuilt
+            .outputs
+            .iter()
+            .all(|o| o.outcome == WriteOutcome::Unchanged)
+    );
+
+    fs::write(root.join("authors.toml"), "[gerald]\nname = \"Gerald\"\n").unwrap();
+    fs::write(
+        root.join("tech/typo.md"),
+        "+++\ntitle = \"Typo\"\ndate = \"2024-01-01\"\nupdate = \"2024-01-01\"\nsummary = \"\"\npath = \"typo.md\"\nauthors = [\"gerlad\"]\n+++\n",
+    )
+    .unwrap();
+    let failed = processor.build_report();
+    assert!(!failed.success);
+    assert!(failed.error.unwrap().contains("unknown author 'gerlad'"));
+    assert_eq!(failed.files.last().unwrap().status, FileStatus::Failed);
+    assert!(failed.outputs.is_empty());
+}
+
+#[test]
+fn test_build_emits_log_events() {
+    use log::{Level, Log, Metadata, Record};
+    use std::sync::Mutex;
+    use zone_builder::logging::{self, LogFormat};
+
+    struct Capture(Mutex<Vec<(Level, String)>>);
+
+    impl Log for Capture {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record) {
+            self.0
+                .lock()
+                .unwrap()
+                .push((record.level(), record.args().to_string()));
+        }
+
+        fn flush(&self) {}
+    }
+
+    static CAPTURE: Capture = Capture(Mutex::new(Vec::new()));
+    log::set_logger(&CAPTURE).expect("Failed to install logger");
+    log::set_max_level(log::LevelFilter::Trace);
+
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+    write_article(&root.join("logged"), "entry.md", "Logged", "2024-01-01");
+    fs::write(
+        root.join("logged/entry.md"),
+        fs::read_to_string(root.join("logged/entry.md")).unwrap() + "\n![x](images/nowhere.png)\n",
+    )
+    .unwrap();
+    fs::write(
+        root.join("logged/typo.md"),
+        "+++\ntitle = \"Typo\"\ndate = 2024-01-02\"\n+++\n",
+    )
+    .unwrap();
+    fs::write(root.join("README.md"), "# About this blog\n").unwrap();
+
+    BlogProcessor::new(root.to_str().unwrap().to_string())
+        .process_directory()
+        .expect("Failed to process directory");
+
+    let events = CAPTURE.0.lock().unwrap().clone();
+    assert!(events.contains(&(
+        Level::Warn,
+        "logged/entry.md: Missing image: images/nowhere.png".to_string()
+    )));
+    assert!(events.contains(&(
+        Level::Debug,
+        "Built logged/entry.md: Logged (2024-01-01)".to_string()
+    )));
+    assert!(
+        events.iter().any(|(level, message)| *level == Level::Warn
+            && message.starts_with("logged/typo.md: Skipped: invalid frontmatter at line 3")),
+        "{:?}",
+        events
+    );
+    assert!(events.contains(&(
+        Level::Debug,
+        "Skipped README.md: no valid frontmatter".to_string()
+    )));
+    assert!(
+        !events
+            .iter()
+            .any(|(level, message)| *level == Level::Warn && message.contains("README.md"))
+    );
+
+    let fields = [("file", "logged/entry.md"), ("rule", "missing-image")];
+    let record = |format| {
+        logging::format_record(
+            &Record::builder()
+                .level(Level::Warn)
+                .target("zone_builder::processor")
+                .args(format_args!("logged/entry.md: Missing image"))
+                .key_values(&fields)
+                .build(),
+            format,
+        )
+    };
+    assert_eq!(
+        record(LogFormat::Text),
+        "warning: logged/entry.md: Missing image"
+    );
+    let json: serde_json::Value = serde_json::from_str(&record(LogFormat::Json)).unwrap();
+    assert_eq!(json["level"], "warn");
+    assert_eq!(json["rule"], "missing-image");
+    assert_eq!(json["file"], "logged/entry.md");
+    assert_eq!(json["message"], "logged/entry.md: Missing image");
+}